@@ -1,12 +1,16 @@
+use std::iter::once;
+
 use data::{
-    Cell, CellIndex, Cells, Constraint, CornerDirection, Edge, EdgeIndex,
-    Edges, IntersectionIndex,
+    Cell, CellIndex, Cells, Constraint, Contradiction, CornerDirection, Edge,
+    EdgeIndex, Edges, IntersectionIndex, Rule, Size, Step,
 };
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use data::EdgeDirection;
+
     // To make our tests as concise as possible, we want an easy and readable
     // way to represent the fields and the edge grids in test code. For
     // representing the edges we choose an ASCII graph as an appropriate option,
@@ -54,16 +58,33 @@ mod tests {
     /// * `input` a string with an ASCII-art representation of both the input
     ///   numbers and the expected output edges
     fn assert_solution(input: &str) {
+        assert_found_edges(input, |fields| solve(fields).unwrap());
+    }
+
+    /// Test helper function which asserts that the deduction rules alone find
+    /// the expected edges, without guessing any values.
+    ///
+    /// * `input` a string with an ASCII-art representation of both the input
+    ///   numbers and the expected deduced edges
+    fn assert_deduction(input: &str) {
+        assert_found_edges(input, |fields| {
+            let mut edges = Edges::create_empty(&fields.get_size());
+            propagate(fields, &mut edges, &mut Vec::new()).unwrap();
+            edges
+        });
+    }
+
+    fn assert_found_edges<F>(input: &str, find_edges: F)
+    where
+        F: Fn(&Cells) -> Edges,
+    {
         let fields = Cells::from_ascii(input);
         let expected_solution = Edges::from_ascii(input);
 
-        let actual_solution = solve(&fields);
-
-        println!("expected solution: ");
-        expected_solution.print_lines();
+        let actual_solution = find_edges(&fields);
 
-        println!("actual solution: ");
-        actual_solution.print_lines();
+        println!("expected solution:\n{}", expected_solution);
+        println!("actual solution:\n{}", actual_solution);
 
         assert_same_lines(&actual_solution, &expected_solution);
     }
@@ -167,9 +188,11 @@ mod tests {
         )
     }
 
+    // This puzzle has no solution at all, but the rules are still able to
+    // deduce the lines next to the twos.
     #[test]
     fn detects_twos_in_corners() {
-        assert_solution(
+        assert_deduction(
             "
             + +-+ + +
              2       
@@ -182,9 +205,11 @@ mod tests {
         );
     }
 
+    // The corners of this puzzle can be deduced, but the rules do not suffice
+    // to find the lines in the middle of it.
     #[test]
     fn detects_threes_in_corners() {
-        assert_solution(
+        assert_deduction(
             "
             +-+ +-+
             |3   3|
@@ -197,6 +222,304 @@ mod tests {
         );
     }
 
+    #[test]
+    fn reports_cell_surrounded_by_too_many_lines() {
+        let input = "
+            +-+
+            |1
+            + +
+        ";
+        let index = CellIndex { row: 0, column: 0 };
+        let mut edges = Edges::from_ascii(input);
+
+        assert_eq!(
+            fill_cell(&Cells::from_ascii(input), &mut edges, index),
+            Err(Contradiction::TooManyLines(index))
+        );
+    }
+
+    #[test]
+    fn reports_intersection_with_more_than_two_lines() {
+        let index = IntersectionIndex { row: 1, column: 1 };
+        let mut edges = Edges::from_ascii(
+            "
+            + + +
+              |
+            +-+-+
+
+            + + +
+        ",
+        );
+
+        assert_eq!(
+            fill_intersection(&mut edges, index),
+            Err(Contradiction::Branch(index))
+        );
+    }
+
+    #[test]
+    fn keeps_loop_closing_edge_that_completes_solution() {
+        let input = "
+            +-+
+            | |
+            + +
+        ";
+        let mut edges = Edges::from_ascii(input);
+
+        assert_eq!(
+            check_loops(&Cells::from_ascii(input), &mut edges),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn rules_out_loop_closing_edge_that_leaves_out_lines() {
+        let input = "
+            +-+ +-+
+            | |
+            + + + +
+        ";
+        let mut edges = Edges::from_ascii(input);
+        let closing_edge = EdgeIndex {
+            direction: EdgeDirection::Horizontal,
+            row: 1,
+            column: 0,
+        };
+
+        assert!(check_loops(&Cells::from_ascii(input), &mut edges)
+            .unwrap()
+            .is_some());
+        assert_eq!(edges[closing_edge], Edge::X);
+    }
+
+    #[test]
+    fn reports_closed_loop_leaving_out_other_lines() {
+        let input = "
+            +-+ +-+
+            | |
+            +-+ + +
+        ";
+        let mut edges = Edges::from_ascii(input);
+
+        assert_eq!(
+            check_loops(&Cells::from_ascii(input), &mut edges),
+            Err(Contradiction::ClosedSubLoop(IntersectionIndex {
+                row: 0,
+                column: 0
+            }))
+        );
+    }
+
+    #[test]
+    fn reports_puzzle_without_solution() {
+        let cells = Cells::from_ascii(
+            "
+            + + + + +
+             2
+            + + + + +
+               2   3
+            + + + + +
+                 2
+            + + + + +
+        ",
+        );
+
+        assert_eq!(solve(&cells), Err(Contradiction::Unsolvable));
+    }
+
+    #[test]
+    fn finds_solutions_up_to_the_limit() {
+        let cells = Cells::from_ascii(
+            "
+            + + +
+
+            + + +
+
+            + + +
+        ",
+        );
+
+        assert_eq!(solve_all(&cells, 3).len(), 3);
+    }
+
+    // Without any numbers, every single loop is a solution, including the empty
+    // one. Closing a loop early must therefore not be ruled out.
+    #[test]
+    fn finds_every_solution_of_puzzle_without_numbers() {
+        let cells = Cells::from_ascii(
+            "
+            + + +
+
+            + + +
+
+            + + +
+        ",
+        );
+
+        assert_eq!(solve_all(&cells, 100).len(), 14);
+    }
+
+    #[test]
+    fn finds_single_solution_of_unique_puzzle() {
+        let input = "
+            +-+-+-+-+-+
+            |        3|
+            +-+ +-+-+-+
+             3| |
+            +-+ +-+ +-+
+            |3 0 3|2|3|
+            +-+ +-+ + +
+             3| |  2| |
+            +-+ +-+-+ +
+            |         |
+            +-+-+-+-+-+
+        ";
+        let solutions = solve_all(&Cells::from_ascii(input), 2);
+
+        assert_eq!(solutions.len(), 1);
+        assert_same_lines(&solutions[0], &Edges::from_ascii(input));
+    }
+
+    #[test]
+    fn detects_puzzles_without_unique_solution() {
+        let ambiguous = Cells::from_ascii(
+            "
+            + + +
+             1
+            + + +
+
+            + + +
+        ",
+        );
+        let unsolvable = Cells::from_ascii(
+            "
+            + +
+             0
+            + +
+             3
+            + +
+        ",
+        );
+
+        assert!(!has_unique_solution(&ambiguous));
+        assert!(!has_unique_solution(&unsolvable));
+    }
+
+    #[test]
+    fn trace_leads_to_solution() {
+        let cells = Cells::from_ascii(
+            "
+            + + + + + +
+             3 1     3
+            + + + + + +
+                   3 1
+            + + + + + +
+
+            + + + + + +
+                   1 3
+            + + + + + +
+             2 2 2 2 2
+            + + + + + +
+        ",
+        );
+        let (solution, trace) = solve_with_trace(&cells).unwrap();
+
+        let mut edges = Edges::create_empty(&cells.get_size());
+        for step in trace {
+            assert!(edges[step.edge].is_unknown());
+            edges[step.edge] = step.value;
+        }
+
+        assert_eq!(edges, solution);
+    }
+
+    #[test]
+    fn trace_names_the_rules_that_were_applied() {
+        let cells = Cells::from_ascii(
+            "
+            + +
+             0
+            + +
+        ",
+        );
+        let (_, trace) = solve_with_trace(&cells).unwrap();
+
+        let index = CellIndex { row: 0, column: 0 };
+        assert_eq!(trace.len(), 4);
+        assert!(trace
+            .iter()
+            .all(|step| step.value == Edge::X
+                && step.rule == Rule::CellCount(index)));
+    }
+
+    #[test]
+    fn trace_contains_constraints_passed_on_diagonally() {
+        let cells = Cells::from_ascii(
+            "
+            + + + +
+             1
+            + + + +
+               2
+            + + + +
+                 3
+            + + + +
+        ",
+        );
+        let (_, trace) = solve_with_trace(&cells).unwrap();
+
+        let passed_on = trace.iter().any(|step| match step.rule {
+            Rule::DiagonalConstraint { .. } => true,
+            _ => false,
+        });
+
+        assert!(passed_on);
+    }
+
+    #[test]
+    fn hints_at_easiest_deduction() {
+        let input = "
+            +-+ + +
+             1
+            + + + +
+               2 3
+            + + + +
+        ";
+        let edges = Edges::from_ascii(input);
+        let step = hint(&Cells::from_ascii(input), &edges).unwrap().unwrap();
+
+        assert_eq!(step.rule, Rule::CellCount(CellIndex { row: 0, column: 0 }));
+        assert_eq!(step.value, Edge::X);
+        assert!(edges[step.edge].is_unknown());
+    }
+
+    #[test]
+    fn hints_nothing_for_solved_puzzle() {
+        let input = "
+            +-+-+
+            |3 3|
+            +-+-+
+        ";
+        let cells = Cells::from_ascii(input);
+        let solution = solve(&cells).unwrap();
+
+        assert_eq!(hint(&cells, &solution), Ok(None));
+    }
+
+    #[test]
+    fn hint_reports_contradiction_in_edges() {
+        let input = "
+            +-+ +
+            |0
+            + + +
+        ";
+        let edges = Edges::from_ascii(input);
+
+        assert_eq!(
+            hint(&Cells::from_ascii(input), &edges),
+            Err(Contradiction::TooManyLines(CellIndex { row: 0, column: 0 }))
+        );
+    }
+
     #[test]
     fn test_solve() {
         let puzzles = vec![
@@ -278,74 +601,74 @@ mod tests {
              0 2| |
             + + +-+ + +
             ",
-            //"
-            //+-+-+ +-+-+-+ +
-            //|3  | |  1  |2
-            //+-+ + + + + +-+
-            //  |2| |1   1 3|
-            //+-+ +-+ +-+-+-+
-            //|3 0 2 2|    2
-            //+-+ +-+-+ + +-+
-            //  | |    1 2|3|
-            //+-+ +-+-+-+-+ +
-            //|3           2|
-            //+-+ +-+-+ +-+-+
-            //  | |   |2|
-            //+-+ + +-+ +-+-+
-            //|3  | |3 1   3|
-            //+-+-+ +-+-+-+-+
-            //",
-            //"
-            //+-+-+-+-+-+-+-+
-            //|3   2 2   1  |
-            //+-+-+-+-+ + + +
-            // 2 2    |  0  |
-            //+-+-+-+ +-+ + +
-            //|  1  |1 2|  1|
-            //+-+ + + + +-+ +
-            //  |1 2|     |2|
-            //+ + +-+ +-+-+ +
-            //  | |3  |2    |
-            //+-+ +-+-+ +-+ +
-            //|2     2 1|3|2|
-            //+ +-+ +-+ + + +
-            //|3|3| | |3| | |
-            //+-+ +-+ +-+ +-+
-            //",
-            //"
-            //+-+-+ +-+-+-+-+
-            //|  2| |3     2|
-            //+ + + +-+-+-+ +
-            //|  1|1     3| |
-            //+ + + +-+-+-+ +
-            //|1  | |  1    |
-            //+ +-+ +-+ + + +
-            //|2|3 1 3|  1 1|
-            //+ +-+-+-+ +-+ +
-            //|2 1   2  | |2|
-            //+-+ +-+-+-+ + +
-            // 2|2|3 1    | |
-            //+ + +-+ +-+-+ +
-            //  |2  |2|    2|
-            //+ +-+-+ +-+-+-+
-            //",
-            //"
-            //+-+-+-+ + + +-+
-            //|3 2 2|1 1  | |
-            //+-+-+ + +-+ + +
-            // 2  |2| | |3|2|
-            //+-+ + +-+ +-+ +
-            //| | |  2 1   2|
-            //+ + + +-+-+ +-+
-            //|2|3| |  3|2|
-            //+ +-+ + +-+ +-+
-            //|1   2| |3    |
-            //+ +-+-+ +-+ +-+
-            //| |    1 3|2|2
-            //+ + +-+-+-+ + +
-            //| |2|  2 2 2|
-            //+-+ +-+-+-+-+ +
-            //",
+            "
+            +-+-+ +-+-+-+ +
+            |3  | |  1  |2
+            +-+ + + + + +-+
+              |2| |1   1 3|
+            +-+ +-+ +-+-+-+
+            |3 0 2 2|    2
+            +-+ +-+-+ + +-+
+              | |    1 2|3|
+            +-+ +-+-+-+-+ +
+            |3           2|
+            +-+ +-+-+ +-+-+
+              | |   |2|
+            +-+ + +-+ +-+-+
+            |3  | |3 1   3|
+            +-+-+ +-+-+-+-+
+            ",
+            "
+            +-+-+-+-+-+-+-+
+            |3   2 2   1  |
+            +-+-+-+-+ + + +
+             2 2    |  0  |
+            +-+-+-+ +-+ + +
+            |  1  |1 2|  1|
+            +-+ + + + +-+ +
+              |1 2|     |2|
+            + + +-+ +-+-+ +
+              | |3  |2    |
+            +-+ +-+-+ +-+ +
+            |2     2 1|3|2|
+            + +-+ +-+ + + +
+            |3|3| | |3| | |
+            +-+ +-+ +-+ +-+
+            ",
+            "
+            +-+-+ +-+-+-+-+
+            |  2| |3     2|
+            + + + +-+-+-+ +
+            |  1|1     3| |
+            + + + +-+-+-+ +
+            |1  | |  1    |
+            + +-+ +-+ + + +
+            |2|3 1 3|  1 1|
+            + +-+-+-+ +-+ +
+            |2 1   2  | |2|
+            +-+ +-+-+-+ + +
+             2|2|3 1    | |
+            + + +-+ +-+-+ +
+              |2  |2|    2|
+            + +-+-+ +-+-+-+
+            ",
+            "
+            +-+-+-+ + + +-+
+            |3 2 2|1 1  | |
+            +-+-+ + +-+ + +
+             2  |2| | |3|2|
+            +-+ + +-+ +-+ +
+            | | |  2 1   2|
+            + + + +-+-+ +-+
+            |2|3| |  3|2|
+            + +-+ + +-+ +-+
+            |1   2| |3    |
+            + +-+-+ +-+ +-+
+            | |    1 3|2|2
+            + + +-+-+-+ + +
+            | |2|  2 2 2|
+            +-+ +-+-+-+-+ +
+            ",
         ];
 
         for puzzle in puzzles {
@@ -355,34 +678,178 @@ mod tests {
 }
 
 /// Checks if the value of any currently unknown edge can be set to be an x
-/// because a line at that edge would create an invalid inner loop.
-fn check_loops(edges: &mut Edges) -> bool {
-    edges.index_edges().any(|index| {
-        if edges[index].is_unknown() && would_close_loop(edges, index) {
-            edges[index] = Edge::X;
-            true
-        } else {
-            false
+/// because a line at that edge would create an invalid inner loop. A loop that
+/// contains all of the lines and satisfies every cell completes the solution,
+/// so it is not ruled out. Returns a `Contradiction` if the lines already form
+/// an invalid loop.
+fn check_loops(
+    cells: &Cells,
+    edges: &mut Edges,
+) -> Result<Option<Step>, Contradiction> {
+    let components = LineComponents::new(edges);
+    let line_count = components.line_count;
+
+    // A closed loop is only allowed if it contains all of the lines.
+    let closed_sub_loop = edges.index_intersections().find(|&index| {
+        let root = components.find(index);
+        components.has_loop(root) && components.line_counts[root] < line_count
+    });
+
+    if let Some(index) = closed_sub_loop {
+        return Err(Contradiction::ClosedSubLoop(index));
+    }
+
+    // A line between two intersections of the same component closes a loop,
+    // which is only allowed if that loop is the solution.
+    let closing_edge = edges.index_edges().find(|&index| {
+        if !edges[index].is_unknown() {
+            return false;
         }
+
+        let intersections = index.get_intersections();
+        let root = components.find(intersections[0]);
+
+        root == components.find(intersections[1])
+            && !(components.line_counts[root] == line_count
+                && would_complete_solution(cells, edges, index))
+    });
+
+    Ok(closing_edge.and_then(|index| {
+        set_edges(edges, once(index), Edge::X, Rule::LoopAvoidance)
+    }))
+}
+
+/// Checks whether a line at the given `EdgeIndex`, which closes a loop that
+/// contains all of the lines, would solve the puzzle. This is the case if
+/// every cell is surrounded by the expected number of lines, because all other
+/// unknown edges would have to be x's.
+fn would_complete_solution(
+    cells: &Cells,
+    edges: &Edges,
+    edge_index: EdgeIndex,
+) -> bool {
+    cells.index_cells().all(|index| {
+        let expected_line_count = cells[&index].get_expected_line_count();
+
+        expected_line_count.map_or(true, |expected_line_count| {
+            let indices = index.index_edges();
+            let (line_count, _) = count_edges(edges, indices.clone());
+            let is_closed_here = indices.into_iter().any(|i| i == edge_index);
+
+            line_count + is_closed_here as usize == expected_line_count
+        })
     })
 }
 
-/// Checks whether a line at the given `EdgeIndex` would form a closed loop in
-/// the `Edges` instance.
-fn would_close_loop(edges: &Edges, edge_index: EdgeIndex) -> bool {
-    let [mut index, end] = edge_index.get_intersections();
+/// The groups of intersections that are connected by lines, which are found
+/// once with a union-find structure, so checking whether a line would close a
+/// loop does not require following the lines again for every edge.
+struct LineComponents {
+    /// The number of intersections in each row.
+    width: usize,
+    /// The parent of each intersection, in row-major order. Intersections that
+    /// are their own parent are the roots of their components.
+    parents: Vec<usize>,
+    /// The number of intersections in the component of each root.
+    sizes: Vec<usize>,
+    /// The number of lines in the component of each root.
+    line_counts: Vec<usize>,
+    /// The total number of lines.
+    line_count: usize,
+}
+
+impl LineComponents {
+    fn new(edges: &Edges) -> LineComponents {
+        let Size { width, height } = edges.get_size();
+        let count = (width + 1) * (height + 1);
+
+        let mut components = LineComponents {
+            width: width + 1,
+            parents: (0..count).collect(),
+            sizes: vec![1; count],
+            line_counts: vec![0; count],
+            line_count: 0,
+        };
 
-    // Initialize with some meaningless initial value.
-    let mut previous: IntersectionIndex = Default::default();
+        for index in edges.index_edges() {
+            if edges[index].is_line() {
+                let intersections = index.get_intersections();
+                components.join(intersections[0], intersections[1]);
+            }
+        }
+
+        components
+    }
+
+    /// Returns the root of the component containing the intersection.
+    fn find(
+        &self,
+        IntersectionIndex { row, column }: IntersectionIndex,
+    ) -> usize {
+        let mut position = row * self.width + column;
+
+        while self.parents[position] != position {
+            position = self.parents[position];
+        }
+
+        position
+    }
+
+    /// Adds a line between the two intersections. The smaller component is
+    /// attached to the larger one, which keeps the paths to the roots short.
+    fn join(&mut self, a: IntersectionIndex, b: IntersectionIndex) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.line_count += 1;
+
+        if a == b {
+            self.line_counts[a] += 1;
+            return;
+        }
+
+        let (root, child) = if self.sizes[a] >= self.sizes[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+
+        self.parents[child] = root;
+        self.sizes[root] += self.sizes[child];
+        self.line_counts[root] += self.line_counts[child] + 1;
+    }
+
+    /// Returns whether the component of the given root contains a closed loop,
+    /// which is the case if it has at least as many lines as intersections.
+    fn has_loop(&self, root: usize) -> bool {
+        self.line_counts[root] > 0 && self.line_counts[root] >= self.sizes[root]
+    }
+}
+
+/// Returns the length of the closed loop running through the intersection
+/// indexed by `start`, or `None` if the intersection is not part of a closed
+/// loop.
+///
+/// The result is only meaningful if no intersection is connected to more than
+/// two lines, otherwise this function may not terminate.
+pub fn get_loop_length(
+    edges: &Edges,
+    start: IntersectionIndex,
+) -> Option<usize> {
+    // Passing the start as the previous intersection lets us follow the line
+    // in whatever direction comes first.
+    let (mut previous, mut index) = (start, start);
+    let mut length = 0;
 
-    // Follow the lines starting at the first_intersection. If we end up at the
-    // second intersection, we know that if the would make the edge a line, we
-    // would form a closed loop.
     while let Some(next) = edges.follow_line(&previous, &index) {
+        length += 1;
+
+        if next == start {
+            return length.into();
+        }
+
         previous = std::mem::replace(&mut index, next);
     }
 
-    index == end
+    None
 }
 
 /// Counts how many of the edges are `Line`s and how many are `X`'s. This
@@ -414,105 +881,176 @@ where
     (line_count, x_count)
 }
 
-/// Sets all of the `Edge`s that are indexed by the `indices` to the given
+/// Sets the first unknown `Edge` that is indexed by the `indices` to the given
 /// `value`. This function is callable with both `Iterator`s over `EdgeIndex`
-/// and over `Option<EdgeIndex>`. Returns whether the value of any of the edges
-/// was changed.
+/// and over `Option<EdgeIndex>`. Returns the `Step` describing the change,
+/// explained by the given `rule`, or `None` if no edge was changed.
 fn set_edges<Index, Indices>(
     edges: &mut Edges,
     indices: Indices,
     value: Edge,
-) -> bool
+    rule: Rule,
+) -> Option<Step>
 where
     Index: Into<Option<EdgeIndex>>,
     Indices: IntoIterator<Item = Index>,
 {
-    indices.into_iter().any(|index| {
-        // An index which is None represents an edge that is out of bounds.
-        // Therefore, its value can not be changed.
-        index.into().map_or(false, |index| match edges[index] {
-            Edge::Unknown => {
-                edges[index] = value;
-                true
-            }
-            _ => false,
-        })
-    })
+    // An index which is None represents an edge that is out of bounds.
+    // Therefore, its value can not be changed.
+    let index = indices
+        .into_iter()
+        .filter_map(Into::into)
+        .find(|&index| edges[index].is_unknown())?;
+
+    edges[index] = value;
+
+    Step {
+        edge: index,
+        value,
+        rule,
+    }
+    .into()
+}
+
+/// Works like `Iterator::find_map`, but stops at the first `Contradiction`
+/// returned by the function `f`.
+fn try_find_map<Items, F, T>(
+    items: Items,
+    mut f: F,
+) -> Result<Option<T>, Contradiction>
+where
+    Items: IntoIterator,
+    F: FnMut(Items::Item) -> Result<Option<T>, Contradiction>,
+{
+    for item in items {
+        if let Some(found) = f(item)? {
+            return Ok(found.into());
+        }
+    }
+
+    Ok(None)
 }
 
 /// Sets the values of the unknown edges adjacent to the cell indexed by `index`
-/// if the known edges provide enough information to do so. Returns whether any
-/// edge was actually changed.
-fn fill_cell(cells: &Cells, edges: &mut Edges, index: CellIndex) -> bool {
+/// if the known edges provide enough information to do so. Returns the `Step`
+/// that changed an edge, if any, or a `Contradiction` if the cell can not be
+/// surrounded by the expected number of lines.
+fn fill_cell(
+    cells: &Cells,
+    edges: &mut Edges,
+    index: CellIndex,
+) -> Result<Option<Step>, Contradiction> {
     let expected_line_count = cells[&index].get_expected_line_count();
 
     // Cells without a number cannot be filled.
-    expected_line_count.map_or(false, |expected_line_count| {
+    expected_line_count.map_or(Ok(None), |expected_line_count| {
         let indices = index.index_edges();
         let (line_count, x_count) = count_edges(edges, indices.clone());
+        let rule = Rule::CellCount(index);
 
-        if line_count == expected_line_count {
+        if line_count > expected_line_count {
+            Err(Contradiction::TooManyLines(index))
+        } else if x_count > 4 - expected_line_count {
+            Err(Contradiction::TooFewLines(index))
+        } else if line_count == expected_line_count {
             // We known already know where all the lines go, so all the other
             // edges must be x's.
-            set_edges(edges, indices, Edge::X)
+            Ok(set_edges(edges, indices, Edge::X, rule))
         } else if x_count == 4 - expected_line_count {
             // We are certain of all the x's and can fill the rest with lines.
-            set_edges(edges, indices, Edge::Line)
+            Ok(set_edges(edges, indices, Edge::Line, rule))
         } else {
-            false
+            Ok(None)
         }
     })
 }
 
 /// Sets the values of the unknown edges adjacent to the intersection indexed by
-/// `index` if the known edges provide enough information to do so. Returns
-/// whether any edge was actually changed.
-fn fill_intersection(edges: &mut Edges, index: IntersectionIndex) -> bool {
-    let (indices, line_count) = {
+/// `index` if the known edges provide enough information to do so. Returns the
+/// `Step` that changed an edge, if any, or a `Contradiction` if the lines at
+/// the intersection can not be part of a loop.
+fn fill_intersection(
+    edges: &mut Edges,
+    index: IntersectionIndex,
+) -> Result<Option<Step>, Contradiction> {
+    let (indices, counts) = {
         let indices = edges.index_adjacent_edges(index);
-        let (line_count, _) = count_edges(edges, indices.clone());
+        let counts = count_edges(edges, indices.clone());
 
         // Collect the values because the iterator immutably borrows from edges,
         // which collides with the mutable borrow further down.
         let indices: Vec<_> = indices.collect();
 
-        (indices, line_count)
+        (indices, counts)
     };
 
     // Most of the intersection-related logic is handled by the constraint code,
     // but this seemed too simple to create a new constraint for (Which would
     // always result in the two edges being set to x's anyway).
-    match line_count {
+    match counts {
+        (line_count, _) if line_count > 2 => Err(Contradiction::Branch(index)),
+        // The only edge which could continue the line is an x.
+        (1, 3) => Err(Contradiction::DeadEnd(index)),
         // We know all the lines of the intersection, we can fill the rest with
         // x's.
-        2 => set_edges(edges, indices, Edge::X),
-        _ => false,
+        (2, _) => Ok(set_edges(
+            edges,
+            indices,
+            Edge::X,
+            Rule::IntersectionDegree(index),
+        )),
+        _ => Ok(None),
     }
 }
 
 /// Iterates through all of the cells and intersections and sets all of the
-/// edges whose values we know for sure. Returns whether the value of any edge
-/// was actually changed.
-fn fill_certain_values(cells: &Cells, edges: &mut Edges) -> bool {
-    let filled_cell = cells
-        .index_cells()
-        .any(|index| fill_cell(cells, edges, index));
+/// edges whose values we know for sure. Returns the `Step` that changed an
+/// edge, if any.
+fn fill_certain_values(
+    cells: &Cells,
+    edges: &mut Edges,
+) -> Result<Option<Step>, Contradiction> {
+    let filled_cell = try_find_map(cells.index_cells(), |index| {
+        fill_cell(cells, edges, index)
+    })?;
 
     // For a better debugging-experience we want this function to change only a
     // single value at a time.
-    filled_cell
-        || edges
-            .index_intersections()
-            .any(|index| fill_intersection(edges, index))
+    match filled_cell {
+        Some(step) => Ok(step.into()),
+        None => try_find_map(edges.index_intersections(), |index| {
+            fill_intersection(edges, index)
+        }),
+    }
 }
 
+/// Returns the `Rule` explaining an edge whose value was determined by a
+/// constraint that was created by the `origin` and passed on diagonally
+/// through the cells indexed by `path`.
+fn explain_constraint(origin: &Rule, path: &[CellIndex]) -> Rule {
+    if path.is_empty() {
+        origin.clone()
+    } else {
+        Rule::DiagonalConstraint {
+            origin: Box::new(origin.clone()),
+            cells: path.to_vec(),
+        }
+    }
+}
+
+/// Applies the constraint to the two edges next to the intersection indexed by
+/// `from` in the given direction, passing it on to the next cell if it does not
+/// determine their values. The `origin` and the `path` of cells the constraint
+/// was already passed through are used to explain the resulting `Step`.
 fn apply_constraint(
     cells: &Cells,
     edges: &mut Edges,
     constraint: Constraint,
     from: IntersectionIndex,
     to: CornerDirection,
-) -> bool {
+    origin: &Rule,
+    path: &mut Vec<CellIndex>,
+) -> Result<Option<Step>, Contradiction> {
     let near = edges.index_adjacent_corner_edges(from, to);
     let counts = count_edges(edges, near.clone());
 
@@ -530,6 +1068,12 @@ fn apply_constraint(
     // to be a line.
     let set_value = {
         let value = match (&constraint, counts) {
+            (Constraint::Line, (2, 0))
+            | (Constraint::Line, (0, 2))
+            | (Constraint::NoCorner, (2, 0))
+            | (Constraint::NoLine, (1, 1)) => {
+                return Err(Contradiction::BrokenConstraint(from));
+            }
             (Constraint::Line, (1, 0))
             | (Constraint::NoCorner, (1, 0))
             | (Constraint::NoLine, (0, 1)) => Edge::X.into(),
@@ -541,51 +1085,70 @@ fn apply_constraint(
 
         // No constraint means no value can be set. Because we explicitly check
         // that the other edge is not set in our match statement, set_edges will
-        // always set the value of an edge and therefore always return true.
-        value.map_or(false, |value| set_edges(edges, near, value))
+        // always set the value of an edge and therefore always return a step.
+        value.and_then(|value| {
+            let rule = explain_constraint(origin, path);
+            set_edges(edges, near, value, rule)
+        })
     };
 
     // If a value was set, be can stop here because the constraint lead to an
     // actual change of an edge, which means that the next cell, if any, will be
     // able to pick up the constraint by just considering its own edges in the
     // next iteration.
-    set_value || {
-        let next_cell = edges.index_diagonally_from_intersection(from, to);
-        next_cell.map_or(false, |next_cell| {
-            apply_constraint_to_cell(cells, edges, constraint, next_cell, to)
-        })
+    if set_value.is_some() {
+        return Ok(set_value);
     }
+
+    let next_cell = edges.index_diagonally_from_intersection(from, to);
+    next_cell.map_or(Ok(None), |next_cell| {
+        path.push(next_cell);
+
+        let step = apply_constraint_to_cell(
+            cells, edges, constraint, next_cell, to, origin, path,
+        )?;
+
+        // Only the cells that actually lead to a change are part of the path.
+        if step.is_none() {
+            path.pop();
+        }
+
+        Ok(step)
+    })
 }
 
 /// Apply a constraint using the number in a cell. Depending on the cell, this
 /// may cause the constraint to be cascaded to nearby intersections and cells.
-/// Returns whether the value of an edge was actually changed.
+/// Returns the `Step` that changed an edge, if any.
 fn apply_constraint_to_cell(
     cells: &Cells,
     edges: &mut Edges,
     constraint: Constraint,
     index: CellIndex,
     to: CornerDirection,
-) -> bool {
+    origin: &Rule,
+    path: &mut Vec<CellIndex>,
+) -> Result<Option<Step>, Contradiction> {
     let near = index.index_corner_edges(to.get_opposite());
     let far = index.index_corner_edges(to);
 
     let next_intersection = index.index_intersection(to);
 
     let (line_count, x_count) = count_edges(edges, far.clone());
+    let rule = explain_constraint(origin, path);
 
     match cells[&index] {
         Cell::One => match constraint {
-            Constraint::Line => set_edges(edges, far, Edge::X),
-            Constraint::NoLine => set_edges(edges, near, Edge::X),
-            _ => false,
+            Constraint::Line => Ok(set_edges(edges, far, Edge::X, rule)),
+            Constraint::NoLine => Ok(set_edges(edges, near, Edge::X, rule)),
+            _ => Ok(None),
         },
         Cell::Two => match constraint {
             Constraint::Line => {
                 if line_count > 0 {
-                    set_edges(edges, far, Edge::X)
+                    Ok(set_edges(edges, far, Edge::X, rule))
                 } else if x_count > 0 {
-                    set_edges(edges, far, Edge::Line)
+                    Ok(set_edges(edges, far, Edge::Line, rule))
                 } else {
                     apply_constraint(
                         cells,
@@ -593,6 +1156,8 @@ fn apply_constraint_to_cell(
                         constraint,
                         next_intersection,
                         to,
+                        origin,
+                        path,
                     )
                 }
             }
@@ -602,65 +1167,83 @@ fn apply_constraint_to_cell(
                 constraint,
                 next_intersection,
                 to,
+                origin,
+                path,
             ),
             Constraint::NoLine => {
                 if x_count > 0 {
-                    set_edges(edges, near, Edge::Line)
+                    Ok(set_edges(edges, near, Edge::Line, rule))
                 } else {
                     let adjacent_directions = to.get_adjacent();
-                    let applied_adjacent =
-                        adjacent_directions.iter().any(|&direction| {
+                    let applied_adjacent = try_find_map(
+                        adjacent_directions.iter(),
+                        |&direction| {
                             apply_constraint(
                                 cells,
                                 edges,
                                 Constraint::Line,
                                 index.index_intersection(direction),
                                 direction,
+                                origin,
+                                path,
                             )
-                        });
-
-                    applied_adjacent
-                        || apply_constraint(
-                            cells,
-                            edges,
-                            Constraint::NoLine,
-                            next_intersection,
-                            to,
-                        )
-                }
-            }
-        },
-        Cell::Three => match constraint {
-            Constraint::Line => set_edges(edges, far, Edge::Line),
-            Constraint::NoCorner => set_edges(edges, far, Edge::Line),
-            Constraint::NoLine => {
-                set_edges(edges, near, Edge::Line)
-                    || apply_constraint(
+                        },
+                    )?;
+
+                    if applied_adjacent.is_some() {
+                        return Ok(applied_adjacent);
+                    }
+
+                    apply_constraint(
                         cells,
                         edges,
-                        Constraint::Line,
+                        Constraint::NoLine,
                         next_intersection,
                         to,
+                        origin,
+                        path,
                     )
+                }
             }
         },
-        _ => false,
+        Cell::Three => match constraint {
+            Constraint::Line => Ok(set_edges(edges, far, Edge::Line, rule)),
+            Constraint::NoCorner => Ok(set_edges(edges, far, Edge::Line, rule)),
+            Constraint::NoLine => {
+                let set_near = set_edges(edges, near, Edge::Line, rule);
+
+                if set_near.is_some() {
+                    return Ok(set_near);
+                }
+
+                apply_constraint(
+                    cells,
+                    edges,
+                    Constraint::Line,
+                    next_intersection,
+                    to,
+                    origin,
+                    path,
+                )
+            }
+        },
+        _ => Ok(None),
     }
 }
 
 /// Checks for constraints in the edges near to the cell indexed by `index` that
-/// can help us solve the puzzle. Returns whether the value of any edge was
-/// actually changed.
+/// can help us solve the puzzle. Returns the `Step` that changed an edge, if
+/// any.
 fn check_cell_constraints(
     cells: &Cells,
     edges: &mut Edges,
     index: CellIndex,
-) -> bool {
+) -> Result<Option<Step>, Contradiction> {
     // Constraints are always created by the number inside of the cell and
     // the value of two adjacent edges surrounding the cell. Because there are
     // four pairs of adjacent edges, every cell can provide up to four
     // constraints.
-    CornerDirection::ALL.iter().any(|&direction| {
+    try_find_map(CornerDirection::ALL.iter(), |&direction| {
         let counts = {
             let indices = index.index_corner_edges(direction);
             count_edges(edges, indices)
@@ -690,29 +1273,41 @@ fn check_cell_constraints(
             _ => None,
         };
 
-        constraint.map_or(false, |constraint| {
+        constraint.map_or(Ok(None), |constraint| {
             // We may have looked at the two edges in the north and west of the
             // cell. The edges effected by the constraint are the ones in the
             // north-west of the cell which is south-east of the current cell.
             let direction = direction.get_opposite();
             let intersection = index.index_intersection(direction);
+            let origin = Rule::CellCorner {
+                cell: index,
+                constraint,
+            };
 
-            apply_constraint(cells, edges, constraint, intersection, direction)
+            apply_constraint(
+                cells,
+                edges,
+                constraint,
+                intersection,
+                direction,
+                &origin,
+                &mut Vec::new(),
+            )
         })
     })
 }
 
 /// Checks for constraints in the edges near to the cell indexed by `index` that
-/// can help us solve the puzzle. Returns whether the value of any edge was
-/// actually changed.
+/// can help us solve the puzzle. Returns the `Step` that changed an edge, if
+/// any.
 fn check_intersection_constraints(
     cells: &Cells,
     edges: &mut Edges,
     index: IntersectionIndex,
-) -> bool {
+) -> Result<Option<Step>, Contradiction> {
     // A constraint at an intersection is created by the value of any pair of
     // adjacent edges next to the intersection.
-    CornerDirection::ALL.iter().any(|&direction| {
+    try_find_map(CornerDirection::ALL.iter(), |&direction| {
         let counts = {
             let indices = edges.index_adjacent_corner_edges(index, direction);
             count_edges(edges, indices)
@@ -749,9 +1344,22 @@ fn check_intersection_constraints(
             _ => None,
         };
 
-        constraint.map_or(false, |constraint| {
+        constraint.map_or(Ok(None), |constraint| {
             let to = direction.get_opposite();
-            apply_constraint(cells, edges, constraint, index, to)
+            let origin = Rule::IntersectionCorner {
+                intersection: index,
+                constraint,
+            };
+
+            apply_constraint(
+                cells,
+                edges,
+                constraint,
+                index,
+                to,
+                &origin,
+                &mut Vec::new(),
+            )
         })
     })
 }
@@ -759,7 +1367,10 @@ fn check_intersection_constraints(
 /// Iterates through all the cells and intersections and detects all the
 /// constraints. A constraint is some piece of information about two edges that
 /// can help us solve the puzzle.
-fn check_constraints(cells: &Cells, edges: &mut Edges) -> bool {
+fn check_constraints(
+    cells: &Cells,
+    edges: &mut Edges,
+) -> Result<Option<Step>, Contradiction> {
     // For example, given the following grid:
     //
     // +x+ +
@@ -774,34 +1385,249 @@ fn check_constraints(cells: &Cells, edges: &mut Edges) -> bool {
     // to be a line. Note that we were able to find this information without
     // knowing the actual value of either a nor b.
 
-    let applied_cell_constraints = cells
-        .index_cells()
-        .any(|index| check_cell_constraints(cells, edges, index));
+    let applied_cell_constraint = try_find_map(cells.index_cells(), |index| {
+        check_cell_constraints(cells, edges, index)
+    })?;
 
     // For a better debugging-experience we want this function to change only a
     // single value at a time.
-    applied_cell_constraints
-        || edges
-            .index_intersections()
-            .any(|index| check_intersection_constraints(cells, edges, index))
+    match applied_cell_constraint {
+        Some(step) => Ok(step.into()),
+        None => try_find_map(edges.index_intersections(), |index| {
+            check_intersection_constraints(cells, edges, index)
+        }),
+    }
 }
 
-/// Returns the solution for a given Suriza puzzle. Makes an attempt to find the
-/// value of all edges but returns an incomplete edge grid if it fails to
-/// do so.
-/// The output is undefined if the input is not a valid Suriza puzzle.
-pub fn solve(cells: &Cells) -> Edges {
+/// Applies the deduction rules once and returns the `Step` that changed the
+/// value of an edge, or `None` if none of the rules was able to do so. Returns
+/// a `Contradiction` if the edges are in a state that can not lead to a valid
+/// solution.
+fn deduce(
+    cells: &Cells,
+    edges: &mut Edges,
+) -> Result<Option<Step>, Contradiction> {
+    if let Some(step) = fill_certain_values(cells, edges)? {
+        return Ok(step.into());
+    }
+
+    if let Some(step) = check_constraints(cells, edges)? {
+        return Ok(step.into());
+    }
+
+    check_loops(cells, edges)
+}
+
+/// Applies the given `rule` at each of the `indices` to its own copy of the
+/// `edges` and returns the easiest `Step` that any of them was able to take.
+fn find_easiest<Indices, F>(
+    edges: &Edges,
+    indices: Indices,
+    mut rule: F,
+) -> Result<Option<Step>, Contradiction>
+where
+    Indices: IntoIterator,
+    F: FnMut(&mut Edges, Indices::Item) -> Result<Option<Step>, Contradiction>,
+{
+    let mut easiest: Option<Step> = None;
+
+    for index in indices {
+        if let Some(step) = rule(&mut edges.clone(), index)? {
+            easiest = get_easier(easiest, step.into());
+        }
+    }
+
+    Ok(easiest)
+}
+
+/// Returns the easier one of the two steps. If both are equally hard, `a` is
+/// returned.
+fn get_easier(a: Option<Step>, b: Option<Step>) -> Option<Step> {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            if b.rule.get_difficulty() < a.rule.get_difficulty() {
+                b.into()
+            } else {
+                a.into()
+            }
+        }
+        (a, b) => a.or(b),
+    }
+}
+
+/// Returns the easiest `Step` that can be deduced from the partially solved
+/// `edges` without guessing, or `None` if none of the rules apply. The `edges`
+/// themselves are not changed. Returns a `Contradiction` if the edges can not
+/// lead to a valid solution.
+pub fn hint(
+    cells: &Cells,
+    edges: &Edges,
+) -> Result<Option<Step>, Contradiction> {
+    // Filling cells and intersections also checks each of them for
+    // contradictions, which the other rules rely on.
+    let filled_cell =
+        find_easiest(edges, cells.index_cells(), |edges, index| {
+            fill_cell(cells, edges, index)
+        })?;
+    let filled_intersection =
+        find_easiest(edges, edges.index_intersections(), fill_intersection)?;
+
+    if let Some(step) = filled_cell.or(filled_intersection) {
+        return Ok(step.into());
+    }
+
+    let cell_constraint =
+        find_easiest(edges, cells.index_cells(), |edges, index| {
+            check_cell_constraints(cells, edges, index)
+        })?;
+    let intersection_constraint =
+        find_easiest(edges, edges.index_intersections(), |edges, index| {
+            check_intersection_constraints(cells, edges, index)
+        })?;
+
+    if let Some(step) = get_easier(cell_constraint, intersection_constraint) {
+        return Ok(step.into());
+    }
+
+    check_loops(cells, &mut edges.clone())
+}
+
+/// Applies the deduction rules until none of them is able to change the value
+/// of an edge anymore, recording every `Step` in the `trace`. Returns a
+/// `Contradiction` if the edges ended up in a state that can not lead to a
+/// valid solution.
+fn propagate(
+    cells: &Cells,
+    edges: &mut Edges,
+    trace: &mut Vec<Step>,
+) -> Result<(), Contradiction> {
+    while let Some(step) = deduce(cells, edges)? {
+        trace.push(step);
+    }
+
+    Ok(())
+}
+
+/// Picks the unknown edge whose value should be guessed next. Edges continuing
+/// the end of a line are preferred, because guessing either value for them
+/// usually allows the rules to deduce a lot of other edges.
+fn choose_guess(edges: &Edges) -> Option<EdgeIndex> {
+    let line_end = edges
+        .index_intersections()
+        .filter(|&index| {
+            let (line_count, _) =
+                count_edges(edges, edges.index_adjacent_edges(index));
+            line_count == 1
+        })
+        .flat_map(|index| edges.index_adjacent_edges(index).flatten())
+        .find(|&index| edges[index].is_unknown());
+
+    line_end.or_else(|| {
+        edges.index_edges().find(|&index| edges[index].is_unknown())
+    })
+}
+
+/// Searches for solutions starting from the given `edges` and adds them to
+/// `solutions` until it contains `limit` of them. Whenever the deduction rules
+/// get stuck, the value of an unknown edge is guessed and the search backtracks
+/// if the guess leads to a contradiction.
+///
+/// The steps taken are recorded in the `trace`. Once the `limit` is reached,
+/// the `trace` holds the steps leading to the last solution that was found.
+fn search(
+    cells: &Cells,
+    mut edges: Edges,
+    solutions: &mut Vec<Edges>,
+    limit: usize,
+    trace: &mut Vec<Step>,
+) {
+    if solutions.len() >= limit || propagate(cells, &mut edges, trace).is_err()
+    {
+        return;
+    }
+
+    // Edges without a contradiction and without any unknown values are a
+    // solution, because each cell is surrounded by the expected number of lines
+    // and all of the lines form a single loop.
+    match choose_guess(&edges) {
+        None => solutions.push(edges),
+        Some(index) => {
+            let trace_length = trace.len();
+
+            // Both guesses lead to different edges, so every solution is only
+            // found once.
+            for &value in &[Edge::Line, Edge::X] {
+                if solutions.len() >= limit {
+                    return;
+                }
+
+                // Forget about the steps of the previous guess.
+                trace.truncate(trace_length);
+                trace.push(Step {
+                    edge: index,
+                    value,
+                    rule: Rule::Guess,
+                });
+
+                let mut guess = edges.clone();
+                guess[index] = value;
+
+                search(cells, guess, solutions, limit, trace);
+            }
+        }
+    }
+}
+
+/// Returns the solution for a given Suriza puzzle. The deduction rules are
+/// applied first, and if they fail to find the value of all edges, the solver
+/// falls back to guessing values and backtracking.
+/// Returns a `Contradiction` if the input has no solution. Contradictions that
+/// can be found without guessing name the offending cell or intersection.
+pub fn solve(cells: &Cells) -> Result<Edges, Contradiction> {
+    solve_with_trace(cells).map(|(edges, _)| edges)
+}
+
+/// Works like `solve`, but additionally returns every `Step` that lead to the
+/// solution in the order in which they were taken.
+pub fn solve_with_trace(
+    cells: &Cells,
+) -> Result<(Edges, Vec<Step>), Contradiction> {
     let mut edges = {
         let size = cells.get_size();
         Edges::create_empty(&size)
     };
+    let mut trace = Vec::new();
 
-    loop {
-        if !fill_certain_values(cells, &mut edges)
-            && !check_constraints(cells, &mut edges)
-            && !check_loops(&mut edges)
-        {
-            break edges;
-        }
+    propagate(cells, &mut edges, &mut trace)?;
+
+    let mut solutions = Vec::new();
+    search(cells, edges, &mut solutions, 1, &mut trace);
+
+    let solution = solutions.pop().ok_or(Contradiction::Unsolvable)?;
+    Ok((solution, trace))
+}
+
+/// Returns up to `limit` different solutions for a given Suriza puzzle. An
+/// empty `Vec` is returned if the input has no solution.
+pub fn solve_all(cells: &Cells, limit: usize) -> Vec<Edges> {
+    let mut solutions = Vec::new();
+    let mut trace = Vec::new();
+
+    let mut edges = {
+        let size = cells.get_size();
+        Edges::create_empty(&size)
+    };
+
+    if propagate(cells, &mut edges, &mut trace).is_ok() {
+        search(cells, edges, &mut solutions, limit, &mut trace);
     }
+
+    solutions
+}
+
+/// Returns whether a given Suriza puzzle has exactly one solution.
+pub fn has_unique_solution(cells: &Cells) -> bool {
+    // There is no need to look for more than two solutions to know that the
+    // solution is not unique.
+    solve_all(cells, 2).len() == 1
 }
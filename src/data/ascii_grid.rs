@@ -0,0 +1,382 @@
+use std::fmt;
+
+use data::{
+    Cell, CellIndex, Cells, Edge, EdgeDirection, EdgeIndex, Edges, ParseError,
+    Size,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_cells_and_edges_from_same_grid() {
+        let grid = AsciiGrid::parse(
+            "+-+ +\n\
+             |3x  \n\
+             +-+x+\n",
+        )
+        .unwrap();
+
+        let mut cells = Cells::create_empty(&grid.get_size());
+        cells[&CellIndex { row: 0, column: 0 }] = Cell::Three;
+
+        let mut edges = Edges::create_empty(&grid.get_size());
+        for &(direction, row, column, value) in &[
+            (EdgeDirection::Horizontal, 0, 0, Edge::Line),
+            (EdgeDirection::Vertical, 0, 0, Edge::Line),
+            (EdgeDirection::Vertical, 0, 1, Edge::X),
+            (EdgeDirection::Horizontal, 1, 0, Edge::Line),
+            (EdgeDirection::Horizontal, 1, 1, Edge::X),
+        ] {
+            edges[EdgeIndex {
+                direction,
+                row,
+                column,
+            }] = value;
+        }
+
+        assert_eq!(grid.get_cells(), cells);
+        assert_eq!(grid.get_edges(), edges);
+    }
+
+    #[test]
+    fn writes_the_same_text_that_was_read() {
+        let input = "\
+            +-+-+-+\n\
+            |3    |\n\
+            + +-+ +\n\
+            |1| |2x\n\
+            +-+ +-+\n";
+
+        let grid = AsciiGrid::parse(input).unwrap();
+
+        let mut written = AsciiGrid::create_empty(&grid.get_size());
+        written.set_cells(&grid.get_cells());
+        written.set_edges(&grid.get_edges());
+
+        assert_eq!(written.to_string(), input);
+    }
+
+    #[test]
+    fn ignores_blank_lines_around_grid() {
+        let grid = AsciiGrid::parse("\n\n+ +\n 2\n+ +\n\n").unwrap();
+
+        assert_eq!(
+            grid.get_size(),
+            Size {
+                width: 1,
+                height: 1
+            }
+        );
+    }
+
+    #[test]
+    fn reports_position_of_unexpected_character() {
+        let error = AsciiGrid::parse("\n+ + +\n 1 4\n+ + +").unwrap_err();
+
+        assert_eq!(
+            error,
+            ParseError::UnexpectedCharacter {
+                line: 3,
+                column: 4,
+                found: '4',
+                expected: "a number from 0 to 3 or ' '",
+            }
+        );
+    }
+
+    #[test]
+    fn reports_lines_longer_than_first_line() {
+        let error = AsciiGrid::parse("+ +\n 1 \n+ +-+").unwrap_err();
+
+        assert_eq!(
+            error,
+            ParseError::UnexpectedCharacter {
+                line: 3,
+                column: 4,
+                found: '-',
+                expected: "the end of the line",
+            }
+        );
+    }
+
+    #[test]
+    fn reports_incomplete_line_of_intersections() {
+        let error = AsciiGrid::parse("+ + +\n\n+ +").unwrap_err();
+
+        assert_eq!(
+            error,
+            ParseError::UnexpectedEndOfLine {
+                line: 3,
+                column: 4,
+                expected: "'-', 'x' or ' '",
+            }
+        );
+    }
+
+    #[test]
+    fn reports_grid_without_closing_line() {
+        let error = AsciiGrid::parse("+ +\n 1\n").unwrap_err();
+
+        assert_eq!(error, ParseError::MissingLine { line: 3 });
+    }
+
+    #[test]
+    fn reports_empty_input() {
+        assert_eq!(AsciiGrid::parse(" \n\n").unwrap_err(), ParseError::Empty);
+        assert_eq!(AsciiGrid::parse("+\n\n+").unwrap_err(), ParseError::Empty);
+        assert_eq!(AsciiGrid::parse("+ +").unwrap_err(), ParseError::Empty);
+    }
+}
+
+const INTERSECTION: &str = "'+'";
+const HORIZONTAL_EDGE: &str = "'-', 'x' or ' '";
+const VERTICAL_EDGE: &str = "'|', 'x' or ' '";
+const CELL: &str = "a number from 0 to 3 or ' '";
+
+/// A Suriza puzzle in the ASCII format that is used to store puzzles as text.
+/// The grid is made up of alternating lines of intersections and lines of
+/// cells, where both the numbers in the cells and the edges between them can
+/// be given:
+///
+/// ```text
+/// +-+-+x+
+/// |3 2| x
+/// +-+ +-+
+/// ```
+///
+/// A '-' or '|' stands for a line, an 'x' for an edge that is known not to be
+/// a line and a ' ' for an unknown edge or a cell without a number.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AsciiGrid {
+    lines: Vec<Vec<char>>,
+}
+
+impl AsciiGrid {
+    /// Creates an `AsciiGrid` without any numbers or edges in it.
+    pub fn create_empty(&Size { width, height }: &Size) -> AsciiGrid {
+        let intersections = (0..=2 * width)
+            .map(|index| if index % 2 == 0 { '+' } else { ' ' })
+            .collect::<Vec<_>>();
+        let cells = vec![' '; 2 * width + 1];
+
+        let lines = (0..=2 * height)
+            .map(|index| {
+                if index % 2 == 0 {
+                    intersections.clone()
+                } else {
+                    cells.clone()
+                }
+            })
+            .collect();
+
+        AsciiGrid { lines }
+    }
+
+    /// Reads an `AsciiGrid` from the given text, checking that every character
+    /// is valid at its position. The width of the grid is taken from its first
+    /// line. Blank lines before and after the grid are ignored, as well as
+    /// whitespace at the end of a line, so lines of cells may be shorter than
+    /// the first line.
+    pub fn parse(input: &str) -> Result<AsciiGrid, ParseError> {
+        // Errors refer to the lines of the whole input, so the line numbers are
+        // kept while leaving out the blank lines around the grid.
+        let numbered_lines: Vec<_> = input
+            .lines()
+            .map(str::trim_end)
+            .enumerate()
+            .skip_while(|&(_, line)| line.is_empty())
+            .collect();
+        let length = numbered_lines
+            .iter()
+            .rposition(|&(_, line)| !line.is_empty())
+            .ok_or(ParseError::Empty)?
+            + 1;
+        let numbered_lines = &numbered_lines[..length];
+
+        // The first line looks like "+ + +", so it contains one character more
+        // than twice the number of cells in each row.
+        let (_, first_line) = numbered_lines[0];
+        let line_length = first_line.chars().count() / 2 * 2 + 1;
+
+        let mut lines = Vec::with_capacity(numbered_lines.len());
+
+        for (line_index, &(number, line)) in numbered_lines.iter().enumerate() {
+            let is_intersection_line = line_index % 2 == 0;
+            let mut characters: Vec<_> = line.chars().collect();
+
+            for (index, &character) in characters.iter().enumerate() {
+                let expected = if index >= line_length {
+                    "the end of the line"
+                } else {
+                    match (is_intersection_line, index % 2 == 0) {
+                        (true, true) if character == '+' => continue,
+                        (true, true) => INTERSECTION,
+                        (true, false) if is_horizontal_edge(character) => {
+                            continue
+                        }
+                        (true, false) => HORIZONTAL_EDGE,
+                        (false, true) if is_vertical_edge(character) => {
+                            continue
+                        }
+                        (false, true) => VERTICAL_EDGE,
+                        (false, false) if is_cell(character) => continue,
+                        (false, false) => CELL,
+                    }
+                };
+
+                return Err(ParseError::UnexpectedCharacter {
+                    line: number + 1,
+                    column: index + 1,
+                    found: character,
+                    expected,
+                });
+            }
+
+            // Lines of cells may end early, because trailing spaces are easily
+            // lost, while every intersection has to be given.
+            if is_intersection_line && characters.len() < line_length {
+                let index = characters.len();
+
+                return Err(ParseError::UnexpectedEndOfLine {
+                    line: number + 1,
+                    column: index + 1,
+                    expected: if index % 2 == 0 {
+                        INTERSECTION
+                    } else {
+                        HORIZONTAL_EDGE
+                    },
+                });
+            }
+
+            characters.resize(line_length, ' ');
+            lines.push(characters);
+        }
+
+        if line_length < 3 {
+            return Err(ParseError::Empty);
+        }
+
+        // Every line of cells has to be followed by a line of intersections.
+        if lines.len() % 2 == 0 {
+            let (number, _) = numbered_lines[length - 1];
+            return Err(ParseError::MissingLine { line: number + 2 });
+        }
+
+        // A single line of intersections does not surround any cells.
+        if lines.len() < 3 {
+            return Err(ParseError::Empty);
+        }
+
+        Ok(AsciiGrid { lines })
+    }
+
+    /// Returns the `Size` of the grid of cells.
+    pub fn get_size(&self) -> Size {
+        let height = self.lines.len() / 2;
+        let width = self.lines[0].len() / 2;
+
+        Size { width, height }
+    }
+
+    /// Returns the numbers in the cells of the grid.
+    pub fn get_cells(&self) -> Cells {
+        let mut cells = Cells::create_empty(&self.get_size());
+
+        for index in cells.index_cells() {
+            let (line, column) = locate_cell(index);
+
+            // The characters were checked when parsing the grid.
+            cells[&index] = Cell::from_ascii(self.lines[line][column]).unwrap();
+        }
+
+        cells
+    }
+
+    /// Returns the edges of the grid.
+    pub fn get_edges(&self) -> Edges {
+        let mut edges = Edges::create_empty(&self.get_size());
+
+        for index in edges.index_edges() {
+            let (line, column) = locate_edge(index);
+
+            // The characters were checked when parsing the grid.
+            edges[index] = Edge::from_ascii(self.lines[line][column]).unwrap();
+        }
+
+        edges
+    }
+
+    /// Writes the numbers of the given `Cells` into the grid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `Cells` do not have the same size as the grid.
+    pub fn set_cells(&mut self, cells: &Cells) {
+        assert_eq!(cells.get_size(), self.get_size());
+
+        for index in cells.index_cells() {
+            let (line, column) = locate_cell(index);
+            self.lines[line][column] = cells[&index].to_ascii();
+        }
+    }
+
+    /// Writes the given `Edges` into the grid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `Edges` do not have the same size as the grid.
+    pub fn set_edges(&mut self, edges: &Edges) {
+        assert_eq!(edges.get_size(), self.get_size());
+
+        for index in edges.index_edges() {
+            let (line, column) = locate_edge(index);
+            self.lines[line][column] = edges[index].to_ascii(index.direction);
+        }
+    }
+}
+
+impl fmt::Display for AsciiGrid {
+    /// Writes the grid line by line, leaving out the spaces at the end of each
+    /// line.
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.lines {
+            let line: String = line.iter().collect();
+            writeln!(formatter, "{}", line.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
+
+fn is_horizontal_edge(character: char) -> bool {
+    character != '|' && Edge::from_ascii(character).is_some()
+}
+
+fn is_vertical_edge(character: char) -> bool {
+    character != '-' && Edge::from_ascii(character).is_some()
+}
+
+fn is_cell(character: char) -> bool {
+    Cell::from_ascii(character).is_some()
+}
+
+/// Returns the line and the column of the character representing the cell.
+fn locate_cell(CellIndex { row, column }: CellIndex) -> (usize, usize) {
+    (2 * row + 1, 2 * column + 1)
+}
+
+/// Returns the line and the column of the character representing the edge.
+fn locate_edge(
+    EdgeIndex {
+        direction,
+        row,
+        column,
+    }: EdgeIndex,
+) -> (usize, usize) {
+    match direction {
+        EdgeDirection::Horizontal => (2 * row, 2 * column + 1),
+        EdgeDirection::Vertical => (2 * row + 1, 2 * column),
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt;
+
+use pyo3::{class::PyObjectProtocol, prelude::*};
+
+use data::{AsciiGrid, Cells, Edges};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn displays_numbers_and_edges_in_same_grid() {
+        let input = "\
+            +-+-+\n\
+            |3 2|\n\
+            +-+-+\n";
+
+        let board =
+            Board::new(input.parse().unwrap(), Some(input.parse().unwrap()));
+
+        assert_eq!(board.to_string(), input);
+    }
+
+    #[test]
+    fn displays_only_numbers_without_edges() {
+        let board = Board::new("+ + +\n|3 2|\n+-+-+".parse().unwrap(), None);
+
+        assert_eq!(board.to_string(), "+ + +\n 3 2\n+ + +\n");
+    }
+}
+
+/// A Suriza puzzle together with the edges that were found for it, which is
+/// displayed as a single grid in the ASCII format.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct Board {
+    cells: Cells,
+    edges: Option<Edges>,
+}
+
+impl Board {
+    /// Creates a `Board` showing the numbers of the given `cells` and, if
+    /// given, the `edges`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `edges` do not have the same size as the `cells`.
+    pub fn new(cells: Cells, edges: Option<Edges>) -> Board {
+        if let Some(ref edges) = edges {
+            assert_eq!(edges.get_size(), cells.get_size());
+        }
+
+        Board { cells, edges }
+    }
+}
+
+#[pymethods]
+impl Board {
+    #[new]
+    fn __new__(
+        obj: &PyRawObject,
+        puzzle: Cells,
+        edges: Option<Edges>,
+    ) -> PyResult<()> {
+        puzzle.check_same_size(edges.as_ref())?;
+
+        obj.init(move |_| Board::new(puzzle, edges))
+    }
+}
+
+#[pyproto]
+impl<'p> PyObjectProtocol<'p> for Board {
+    fn __str__(&self) -> PyResult<String> {
+        Ok(self.to_string())
+    }
+
+    fn __repr__(&self) -> PyResult<String> {
+        let size = self.cells.get_size();
+
+        Ok(format!(
+            "Board(width={}, height={})",
+            size.width, size.height
+        ))
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut grid = AsciiGrid::create_empty(&self.cells.get_size());
+        grid.set_cells(&self.cells);
+
+        if let Some(ref edges) = self.edges {
+            grid.set_edges(edges);
+        }
+
+        write!(formatter, "{}", grid)
+    }
+}
@@ -27,17 +27,43 @@ impl Cell {
             Three => 3.into(),
         }
     }
-}
 
-impl<'a> From<&'a str> for Cell {
-    fn from(value: &'a str) -> Self {
-        match value {
-            "" | " " => Any,
-            "0" => Zero,
-            "1" => One,
-            "2" => Two,
-            "3" => Three,
-            _ => panic!(),
+    /// Creates a `Cell` from a character of the ASCII format, which is either
+    /// a number from 0 to 3 or a ' ' for a cell without a number. Returns
+    /// `None` for any other character.
+    pub fn from_ascii(character: char) -> Option<Cell> {
+        match character {
+            ' ' => Any,
+            '0' => Zero,
+            '1' => One,
+            '2' => Two,
+            '3' => Three,
+            _ => return None,
+        }
+        .into()
+    }
+
+    /// Creates a `Cell` from a string as it is passed from Python, which is
+    /// either a number from 0 to 3 or a blank or empty string for a cell
+    /// without a number. Returns `None` for any other string.
+    pub fn from_value(value: &str) -> Option<Cell> {
+        let mut characters = value.chars();
+
+        match (characters.next(), characters.next()) {
+            (None, _) => Any.into(),
+            (Some(character), None) => Self::from_ascii(character),
+            _ => None,
+        }
+    }
+
+    /// Returns the character representing this cell in the ASCII format.
+    pub fn to_ascii(self) -> char {
+        match self {
+            Any => ' ',
+            Zero => '0',
+            One => '1',
+            Two => '2',
+            Three => '3',
         }
     }
 }
@@ -45,6 +71,15 @@ impl<'a> From<&'a str> for Cell {
 impl<'a> FromPyObject<'a> for Cell {
     fn extract(object: &'a PyObjectRef) -> PyResult<Self> {
         let value = String::extract(object)?;
-        Ok(Self::from(value.as_str()))
+
+        Self::from_value(&value).ok_or_else(|| {
+            exc::ValueError::new(format!("invalid cell value: {:?}", value))
+        })
+    }
+}
+
+impl IntoPyObject for Cell {
+    fn into_object(self, py: Python) -> PyObject {
+        self.to_ascii().to_string().into_object(py)
     }
 }
@@ -1,14 +1,13 @@
 #[cfg(test)]
 extern crate unindent;
 
-#[cfg(test)]
-use std::iter::repeat;
-
-use std::ops::Index;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
 
 use pyo3::prelude::*;
 
-use data::{Cell, CellIndex, Size};
+use data::{AsciiGrid, Cell, CellIndex, Edges, ParseError, PuzzleError, Size};
 
 #[cfg(test)]
 mod tests {
@@ -33,6 +32,42 @@ mod tests {
         assert_eq!(rows, expected_rows);
     }
 
+    #[test]
+    fn creates_cells_from_rows_of_equal_length() {
+        let rows = vec![vec![One, Any], vec![Three, Zero]];
+
+        assert_eq!(Cells::from_rows(rows.clone()), Ok(Cells { rows }));
+    }
+
+    #[test]
+    fn reports_rows_without_cells() {
+        assert_eq!(Cells::from_rows(vec![]), Err(PuzzleError::Empty));
+        assert_eq!(Cells::from_rows(vec![vec![]]), Err(PuzzleError::Empty));
+    }
+
+    #[test]
+    fn reports_rows_of_different_length() {
+        let rows = vec![vec![One, Any], vec![Two, Any], vec![Three]];
+
+        assert_eq!(
+            Cells::from_rows(rows),
+            Err(PuzzleError::RaggedRow {
+                row: 2,
+                length: 1,
+                expected: 2
+            })
+        );
+    }
+
+    #[test]
+    fn maps_values_to_cells() {
+        assert_eq!(Cell::from_value(""), Some(Any));
+        assert_eq!(Cell::from_value(" "), Some(Any));
+        assert_eq!(Cell::from_value("2"), Some(Two));
+        assert_eq!(Cell::from_value("4"), None);
+        assert_eq!(Cell::from_value("12"), None);
+    }
+
     #[test]
     fn returns_correct_size() {
         let cells = {
@@ -48,65 +83,95 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn checks_size_of_edges() {
+        let cells: Cells = "+ + +\n|   |\n+ + +".parse().unwrap();
+        let same: Edges = "+ + +\n     \n+ + +".parse().unwrap();
+        let other: Edges = "+ +\n   \n+ +".parse().unwrap();
+
+        assert!(cells.check_same_size(None).is_ok());
+        assert!(cells.check_same_size(Some(&same)).is_ok());
+        assert!(cells.check_same_size(Some(&other)).is_err());
+    }
 }
 
 /// A struct representing a rectangular grid of cells forming a Suriza puzzle.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Cells {
     rows: Vec<Vec<Cell>>,
 }
 
 impl Cells {
+    /// Creates a `Cells` instance without any numbers in it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either the `width` or `height` of the given `Size` are equal
+    /// to zero.
+    pub fn create_empty(&Size { width, height }: &Size) -> Cells {
+        assert!(width != 0);
+        assert!(height != 0);
+
+        let rows = vec![vec![Cell::Any; width]; height];
+
+        Cells { rows }
+    }
+
+    /// Creates a `Cells` instance from the given rows of cells. Returns a
+    /// `PuzzleError` if there are no cells or if the rows differ in length.
+    pub fn from_rows(rows: Vec<Vec<Cell>>) -> Result<Cells, PuzzleError> {
+        let width = match rows.first() {
+            Some(first_row) if !first_row.is_empty() => first_row.len(),
+            _ => return Err(PuzzleError::Empty),
+        };
+
+        let ragged_row = rows.iter().position(|row| row.len() != width);
+        if let Some(row) = ragged_row {
+            return Err(PuzzleError::RaggedRow {
+                row,
+                length: rows[row].len(),
+                expected: width,
+            });
+        }
+
+        Ok(Cells { rows })
+    }
+
     /// Creates a `Cells` instance from a `&str` containing an ASCII-image
     /// representing the numbers in the grid.
     ///
     /// This method is used internally in this crate to create more readable
     /// test cases. For convenient usage this method removes any indentation
     /// from the input string before further processing.
-    /// No effort is put into making this function detect and report any invalid
-    /// inputs.
     ///
     /// # Panics
     ///
-    /// May panic on unexpected inputs, but does not guarantee to do so.
+    /// Panics if the input is not a valid grid.
     #[cfg(test)]
     pub fn from_ascii(input: &str) -> Cells {
-        let trimmed_input = unindent::unindent(input);
-        let mut lines = trimmed_input.lines();
-
-        // Input is not valid if it does not have at least one row.
-        let first_line = lines.next().unwrap();
-
-        // The first line might look like this "+ + + + +". We care for the
-        // number of fields, which is equal to the number of spaces, which in
-        // turn is equal to the number of characters in the first line divided
-        // by two, rounded towards zero.
-        let numbers_per_row = first_line.len() / 2;
-
-        let rows = lines
-            .step_by(2) // Skip the lines containing the horizontal edges.
-            .map(|line| {
-                line.chars()
-                    .skip(1) // Skip the columns containing the vertical edges.
-                    .step_by(2) //
-                    .map(|value| Cell::from(value.to_string().as_str()))
-                    .chain(repeat(Cell::Any)) // Fill up cells for short lines so
-                    .take(numbers_per_row) // all rows have the same width.
-                    .collect()
-            })
-            .collect();
-
-        Cells { rows }
+        unindent::unindent(input).parse().unwrap()
     }
 
     /// Returns the `Size` of the `Cells` instance.
     pub fn get_size(&self) -> Size {
         let height = self.rows.len();
-        let width = self.rows[0].len();
+        let width = self.rows.first().map_or(0, Vec::len);
 
         Size { width, height }
     }
 
+    /// Returns a `ValueError` if the `edges` are given but do not have the same
+    /// size as the `Cells`, e.g. because they belong to a different puzzle.
+    pub fn check_same_size(&self, edges: Option<&Edges>) -> PyResult<()> {
+        match edges {
+            Some(edges) if edges.get_size() != self.get_size() => {
+                Err(exc::ValueError::new("edges do not match the puzzle"))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Returns an `Iterator` over the indices to all cells in this `Cell`
     /// instance.
     pub fn index_cells(&self) -> impl Iterator<Item = CellIndex> {
@@ -125,9 +190,65 @@ impl<'a> Index<&'a CellIndex> for Cells {
     }
 }
 
+impl<'a> IndexMut<&'a CellIndex> for Cells {
+    fn index_mut(
+        &mut self,
+        &CellIndex { row, column }: &CellIndex,
+    ) -> &mut Cell {
+        &mut self.rows[row][column]
+    }
+}
+
+impl fmt::Display for Cells {
+    /// Writes the numbers as a grid in the ASCII format without any edges.
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut grid = AsciiGrid::create_empty(&self.get_size());
+        grid.set_cells(self);
+
+        write!(formatter, "{}", grid)
+    }
+}
+
+impl FromStr for Cells {
+    type Err = ParseError;
+
+    /// Reads the numbers from a grid in the ASCII format, ignoring any edges
+    /// drawn in it.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        AsciiGrid::parse(input).map(|grid| grid.get_cells())
+    }
+}
+
 impl<'a> FromPyObject<'a> for Cells {
+    /// Extracts `Cells` from a list of rows, each of which contains strings
+    /// with either a number from 0 to 3 or a blank for cells without a number.
     fn extract(object: &'a PyObjectRef) -> PyResult<Self> {
-        let rows = Vec::<_>::extract(object)?;
-        Ok(Self { rows })
+        let values = Vec::<Vec<String>>::extract(object)?;
+        let mut rows = Vec::with_capacity(values.len());
+
+        for (row, row_values) in values.into_iter().enumerate() {
+            let cells =
+                row_values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(column, value)| {
+                        Cell::from_value(&value).ok_or(
+                            PuzzleError::InvalidCell { row, column, value },
+                        )
+                    })
+                    .collect::<Result<_, _>>()?;
+
+            rows.push(cells);
+        }
+
+        Self::from_rows(rows).map_err(Into::into)
+    }
+}
+
+impl IntoPyObject for Cells {
+    /// Converts the cells into a `list` of rows in the same format that is
+    /// accepted as a puzzle.
+    fn into_object(self, py: Python) -> PyObject {
+        self.rows.into_object(py)
     }
 }
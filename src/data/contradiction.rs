@@ -0,0 +1,79 @@
+use std::{error::Error, fmt};
+
+use pyo3::{exc::ValueError, PyErr};
+
+use data::{CellIndex, IntersectionIndex};
+
+use self::Contradiction::*;
+
+/// `Enum` that describes why the values of the edges in a Suriza puzzle can not
+/// be part of a valid solution.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Contradiction {
+    /// The cell is surrounded by more lines than its number allows.
+    TooManyLines(CellIndex),
+    /// The cell is surrounded by so many x's that it can never be surrounded by
+    /// the number of lines it requires.
+    TooFewLines(CellIndex),
+    /// More than two lines meet at the intersection.
+    Branch(IntersectionIndex),
+    /// A single line ends at the intersection and can not be continued.
+    DeadEnd(IntersectionIndex),
+    /// The intersection is part of a closed loop which leaves other lines
+    /// outside of it.
+    ClosedSubLoop(IntersectionIndex),
+    /// Two edges next to the intersection violate a constraint that was
+    /// derived from the surrounding cells.
+    BrokenConstraint(IntersectionIndex),
+    /// Every possible value of the unknown edges leads to a contradiction, so
+    /// the puzzle has no solution.
+    Unsolvable,
+}
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TooManyLines(CellIndex { row, column }) => write!(
+                formatter,
+                "cell at row {}, column {} is surrounded by too many lines",
+                row, column
+            ),
+            TooFewLines(CellIndex { row, column }) => write!(
+                formatter,
+                "cell at row {}, column {} can not be surrounded by enough lines",
+                row, column
+            ),
+            Branch(IntersectionIndex { row, column }) => write!(
+                formatter,
+                "more than two lines meet at intersection at row {}, column {}",
+                row, column
+            ),
+            DeadEnd(IntersectionIndex { row, column }) => write!(
+                formatter,
+                "line ends at intersection at row {}, column {}",
+                row, column
+            ),
+            ClosedSubLoop(IntersectionIndex { row, column }) => write!(
+                formatter,
+                "closed loop through intersection at row {}, column {} does \
+                 not contain all lines",
+                row, column
+            ),
+            BrokenConstraint(IntersectionIndex { row, column }) => write!(
+                formatter,
+                "edges next to intersection at row {}, column {} violate a \
+                 constraint of the surrounding cells",
+                row, column
+            ),
+            Unsolvable => write!(formatter, "puzzle has no solution"),
+        }
+    }
+}
+
+impl Error for Contradiction {}
+
+impl From<Contradiction> for PyErr {
+    fn from(contradiction: Contradiction) -> Self {
+        ValueError::new(contradiction.to_string())
+    }
+}
@@ -0,0 +1,43 @@
+use pyo3::prelude::*;
+
+use self::Difficulty::*;
+
+/// `Enum` that represents how hard it is to solve a Suriza puzzle by hand.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub enum Difficulty {
+    /// The puzzle can be solved by counting lines and applying constraints at
+    /// the corners of cells and intersections.
+    Easy,
+    /// The puzzle requires following constraints diagonally across cells or
+    /// avoiding closed loops.
+    Medium,
+    /// The puzzle can only be solved by guessing and backtracking.
+    Hard,
+}
+
+impl Difficulty {
+    /// Returns a short name for the difficulty, e.g. for use as a file name.
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Easy => "easy",
+            Medium => "medium",
+            Hard => "hard",
+        }
+    }
+}
+
+impl<'a> FromPyObject<'a> for Difficulty {
+    fn extract(object: &'a PyObjectRef) -> PyResult<Self> {
+        let value = String::extract(object)?;
+
+        match value.as_str() {
+            "easy" => Ok(Easy),
+            "medium" => Ok(Medium),
+            "hard" => Ok(Hard),
+            _ => Err(exc::ValueError::new(format!(
+                "unknown difficulty '{}', expected 'easy', 'medium' or 'hard'",
+                value
+            ))),
+        }
+    }
+}
@@ -1,19 +1,37 @@
+use pyo3::prelude::*;
+
+use data::EdgeDirection;
+
 use self::Edge::*;
 
-#[cfg(tests)]
+#[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn maps_horizontal_edges_from_ascii() {
-        assert_eq!(Edge::from_ascii('-'), Line);
+        assert_eq!(Edge::from_ascii('-'), Some(Line));
     }
 
+    #[test]
     fn maps_vertical_edges_from_ascii() {
-        assert_eq(!Edge::from_ascii('|'), Line);
+        assert_eq!(Edge::from_ascii('|'), Some(Line));
+    }
+
+    #[test]
+    fn maps_crossed_out_edges_from_ascii() {
+        assert_eq!(Edge::from_ascii('x'), Some(X));
     }
 
-    // We may choose to use for example 'X' to signify a crossed-out edge.
-    fn accepts_arbitrary_characters_as_ascii() {
-        assert_eq!(Edge::from_ascii('X'), Unknown);
+    #[test]
+    fn rejects_arbitrary_characters_as_ascii() {
+        assert_eq!(Edge::from_ascii('X'), None);
+    }
+
+    #[test]
+    fn maps_lines_to_ascii_by_direction() {
+        assert_eq!(Line.to_ascii(EdgeDirection::Horizontal), '-');
+        assert_eq!(Line.to_ascii(EdgeDirection::Vertical), '|');
     }
 }
 
@@ -25,15 +43,27 @@ pub enum Edge {
 }
 
 impl Edge {
-    /// Creates an `Edge` from the given `character`.
-    ///
-    /// This method is used internally as a helper to create more readable
-    /// tests.
-    #[cfg(test)]
-    pub fn from_ascii(character: char) -> Edge {
+    /// Creates an `Edge` from the given `character`, where '-' and '|'
+    /// represent lines, 'x' represents an x and ' ' represents an unknown edge.
+    /// Returns `None` for any other character.
+    pub fn from_ascii(character: char) -> Option<Edge> {
         match character {
+            ' ' => Unknown,
+            'x' => X,
             '-' | '|' => Line,
-            _ => Unknown,
+            _ => return None,
+        }
+        .into()
+    }
+
+    /// Returns the character representing this edge in the ASCII format. Lines
+    /// are drawn as '-' or '|' depending on the `direction` of the edge.
+    pub fn to_ascii(self, direction: EdgeDirection) -> char {
+        match (self, direction) {
+            (Unknown, _) => ' ',
+            (X, _) => 'x',
+            (Line, EdgeDirection::Horizontal) => '-',
+            (Line, EdgeDirection::Vertical) => '|',
         }
     }
 
@@ -51,3 +81,21 @@ impl Edge {
         }
     }
 }
+
+impl<'a> FromPyObject<'a> for Edge {
+    /// Extracts an `Edge` from a string, where '-' and '|' represent lines, 'x'
+    /// represents an x and ' ' or an empty string represent an unknown edge.
+    fn extract(object: &'a PyObjectRef) -> PyResult<Self> {
+        let value = String::extract(object)?;
+
+        match value.as_str() {
+            "" | " " => Ok(Unknown),
+            "x" => Ok(X),
+            "-" | "|" => Ok(Line),
+            _ => Err(exc::ValueError::new(format!(
+                "invalid edge value: {:?}",
+                value
+            ))),
+        }
+    }
+}
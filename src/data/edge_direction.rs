@@ -1,5 +1,25 @@
+use pyo3::prelude::*;
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum EdgeDirection {
     Horizontal,
     Vertical,
 }
+
+impl<'a> FromPyObject<'a> for EdgeDirection {
+    /// Extracts an `EdgeDirection` from its name, which is either "horizontal"
+    /// or "vertical" like in the steps of a solution.
+    fn extract(object: &'a PyObjectRef) -> PyResult<Self> {
+        let value = String::extract(object)?;
+
+        match value.as_str() {
+            "horizontal" => Ok(EdgeDirection::Horizontal),
+            "vertical" => Ok(EdgeDirection::Vertical),
+            _ => Err(exc::ValueError::new(format!(
+                "unknown edge direction '{}', expected 'horizontal' or \
+                 'vertical'",
+                value
+            ))),
+        }
+    }
+}
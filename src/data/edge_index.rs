@@ -1,3 +1,5 @@
+use pyo3::prelude::*;
+
 use data::{
     edge_direction::{
         EdgeDirection,
@@ -36,3 +38,18 @@ impl EdgeIndex {
         [IntersectionIndex { row, column }, next]
     }
 }
+
+impl<'a> FromPyObject<'a> for EdgeIndex {
+    /// Extracts an `EdgeIndex` from a tuple of its direction, row and column,
+    /// in the same format as the edges in the steps of a solution.
+    fn extract(object: &'a PyObjectRef) -> PyResult<Self> {
+        let (direction, row, column) =
+            <(EdgeDirection, usize, usize)>::extract(object)?;
+
+        Ok(EdgeIndex {
+            direction,
+            row,
+            column,
+        })
+    }
+}
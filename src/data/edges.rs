@@ -4,19 +4,19 @@ extern crate itertools;
 #[cfg(test)]
 extern crate unindent;
 
-#[cfg(test)]
-use std::iter::repeat;
-
+use std::fmt;
 use std::iter::once;
-use std::mem::replace;
 use std::ops::{Index, IndexMut};
+use std::str::FromStr;
 
 use self::boolinator::Boolinator;
 
+use pyo3::prelude::*;
+
 use data::{
-    CellIndex, CornerDirection, Direction, Edge, Edge::*, EdgeDirection::*,
-    EdgeIndex, HorizontalDirection::*, IntersectionIndex, Size,
-    VerticalDirection::*,
+    AsciiGrid, CellIndex, CornerDirection, Direction, Edge, Edge::*,
+    EdgeDirection::*, EdgeIndex, HorizontalDirection::*, IntersectionIndex,
+    ParseError, Size, VerticalDirection::*,
 };
 
 #[cfg(test)]
@@ -149,6 +149,55 @@ mod tests {
 
         assert!(correctly_mapped);
     }
+
+    #[test]
+    fn returns_no_routes_without_lines() {
+        let edges = Edges::from_ascii(
+            "
+            + +
+
+            + +
+        ",
+        );
+
+        assert!(edges.get_routes().is_empty());
+        assert!(edges.get_route().is_empty());
+    }
+
+    #[test]
+    fn returns_route_for_each_loop() {
+        let edges = Edges::from_ascii(
+            "
+            +-+ +-+
+            | | | |
+            +-+ +-+
+        ",
+        );
+
+        assert_eq!(
+            edges.get_routes(),
+            vec![
+                vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)],
+                vec![(2, 0), (3, 0), (3, 1), (2, 1), (2, 0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn returns_open_paths_from_end_to_end() {
+        let edges = Edges::from_ascii(
+            "
+            + +-+
+              | |
+            +-+ +
+        ",
+        );
+
+        assert_eq!(
+            edges.get_routes(),
+            vec![vec![(0, 1), (1, 1), (1, 0), (2, 0), (2, 1)]]
+        );
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -184,58 +233,21 @@ impl Edges {
     /// test cases. For convenient usage this method removes any indentation
     /// from the input string before further processing. Horizontal lines are
     /// represented by a '-', vertical lines by a '|' character.
-    /// No effort is put into making this function detect and report any invalid
-    /// inputs.
     ///
     /// # Panics
     ///
-    /// May panic on unexpected inputs, but does not guarantee to do so.
+    /// Panics if the input is not a valid grid.
     #[cfg(test)]
     pub fn from_ascii(input: &str) -> Edges {
-        let input = unindent::unindent(input);
-
-        // Iterating via .lines() means searching for '\n' characters. It seems
-        // clearer to just do it once and store the result in a Vec.
-        let lines: Vec<_> = input.lines().collect();
-
-        // The first line may look like this "+ + + + +". We care for the number
-        // of '+' characters, which is the total amount of characters divided by
-        // two, rounded away from zero.
-        // Because an empty input is not valid and this is intended for internal
-        // use only, it is okay to index into the Vec here before checking its
-        // size before.
-        let width = lines[0].len() / 2 + 1;
-
-        let horizontal = lines
-            .iter()
-            .step_by(2) // Select rows containing horizontal edges
-            .map(|line| {
-                line.chars()
-                    .skip(1) // Select characters representing horizontal edges
-                    .step_by(2) //
-                    .map(Edge::from_ascii)
-                    .collect()
-            })
-            .collect();
+        unindent::unindent(input).parse().unwrap()
+    }
 
-        let vertical = lines
-            .iter()
-            .skip(1) // Select rows containing vertical edges
-            .step_by(2) //
-            .map(|line| {
-                line.chars()
-                    .step_by(2) // Select characters representing vertical edges
-                    .map(Edge::from_ascii)
-                    .chain(repeat(Unknown)) // Fill up cells for short lines
-                    .take(width)
-                    .collect()
-            })
-            .collect();
+    /// Returns the `Size` of the grid of cells surrounded by the edges.
+    pub fn get_size(&self) -> Size {
+        let height = self.vertical.len();
+        let width = self.horizontal[0].len();
 
-        Edges {
-            horizontal,
-            vertical,
-        }
+        Size { width, height }
     }
 
     /// Returns a `Option<EdgeIndex>` representing the edge adjacent to the
@@ -365,42 +377,6 @@ impl Edges {
             .map(|(row, column)| IntersectionIndex { row, column })
     }
 
-    #[cfg(test)]
-    pub fn print_lines(&self) {
-        use itertools::Itertools;
-
-        // [Line, X, Unknown] -> "+-+x+ +"
-        let horizontal_lines = self.horizontal.iter().map(|row| {
-            let inner = row
-                .iter()
-                .map(|edge| match edge {
-                    Edge::Line => '-',
-                    Edge::X => 'x',
-                    Edge::Unknown => ' ',
-                })
-                .join("+");
-
-            format!("+{}+", inner)
-        });
-
-        // [Line, X, Unknown] -> "| x  "
-        let vertical_lines = self.vertical.iter().map(|row| {
-            row.iter()
-                .map(|edge| match edge {
-                    Edge::Line => '|',
-                    Edge::X => 'x',
-                    Edge::Unknown => ' ',
-                })
-                .join(" ")
-        });
-
-        // Write the lines in alternating order, beginning with a horizontal
-        // line.
-        for line in horizontal_lines.interleave(vertical_lines) {
-            println!("{}", line);
-        }
-    }
-
     pub fn index_adjacent_intersection(
         &self,
         IntersectionIndex { row, column }: IntersectionIndex,
@@ -485,41 +461,75 @@ impl Edges {
         CellIndex { row, column }.into()
     }
 
+    /// Returns the first of the routes returned by `get_routes`, which is the
+    /// whole solution if the edges form a single loop. Returns an empty route
+    /// if there are no lines.
     pub fn get_route(&self) -> Vec<(usize, usize)> {
-        let mut intersection_indices = self.index_intersections();
-
-        let mut previous = IntersectionIndex { row: 0, column: 0 };
-        let mut index = intersection_indices
-            .find(|intersection_index| {
-                let edge_indices = Direction::iter_all().map(|direction| {
-                    self.index_adjacent_edge(intersection_index, direction)
-                });
-
-                edge_indices
-                    .flatten()
-                    .any(|edge_index| self[edge_index].is_line())
-            })
-            .unwrap();
+        self.get_routes().into_iter().next().unwrap_or_default()
+    }
 
-        let mut route = vec![index];
-        while let Some(next) = self.follow_line(&previous, &index) {
-            if next == route[0] {
-                break;
-            }
+    /// Returns a route along each connected component of lines, as a list of
+    /// intersections given as column and row. Closed loops end where they
+    /// started, while open paths lead from one of their ends to the other.
+    /// Intersections where more than two lines meet split the component into
+    /// several routes.
+    pub fn get_routes(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut remaining = self.clone();
+        let mut routes = Vec::new();
+
+        // Open paths have to be traced from one of their ends, which are the
+        // intersections with an odd number of lines, or they would be split
+        // in two.
+        let ends: Vec<_> = self
+            .index_intersections()
+            .filter(|&index| self.count_lines(index) % 2 == 1)
+            .collect();
 
-            previous = replace(&mut index, next);
-            route.push(index);
+        for start in ends.into_iter().chain(self.index_intersections()) {
+            while remaining.count_lines(start) > 0 {
+                routes.push(remaining.remove_route(start));
+            }
         }
 
-        let mut route: Vec<_> = route
-            .iter()
-            .map(|index| (index.column, index.row))
-            .collect();
+        routes
+    }
+
+    /// Returns the number of lines adjacent to the intersection.
+    fn count_lines(&self, index: IntersectionIndex) -> usize {
+        self.index_adjacent_edges(index)
+            .flatten()
+            .filter(|&edge_index| self[edge_index].is_line())
+            .count()
+    }
+
+    /// Follows the lines starting at the given intersection until there is no
+    /// line left to follow, removing each of them on the way. Returns the
+    /// intersections that were visited.
+    fn remove_route(
+        &mut self,
+        start: IntersectionIndex,
+    ) -> Vec<(usize, usize)> {
+        let mut index = start;
+        let mut route = vec![(index.column, index.row)];
+
+        loop {
+            let next = Direction::iter_all()
+                .filter_map(|direction| {
+                    let edge_index =
+                        self.index_adjacent_edge(&index, direction)?;
+                    self[edge_index].is_line().as_some((edge_index, direction))
+                })
+                .next();
 
-        let first = route[0];
-        route.push(first);
+            let (edge_index, direction) = match next {
+                Some(next) => next,
+                None => return route,
+            };
 
-        route
+            self[edge_index] = Unknown;
+            index = self.index_adjacent_intersection(index, direction).unwrap();
+            route.push((index.column, index.row));
+        }
     }
 }
 
@@ -556,3 +566,71 @@ impl IndexMut<EdgeIndex> for Edges {
         }
     }
 }
+
+impl fmt::Display for Edges {
+    /// Writes the edges as a grid in the ASCII format, e.g. "+-+x+".
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut grid = AsciiGrid::create_empty(&self.get_size());
+        grid.set_edges(self);
+
+        write!(formatter, "{}", grid)
+    }
+}
+
+impl FromStr for Edges {
+    type Err = ParseError;
+
+    /// Reads the edges from a grid in the ASCII format, ignoring any numbers
+    /// written in it.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        AsciiGrid::parse(input).map(|grid| grid.get_edges())
+    }
+}
+
+impl<'a> FromPyObject<'a> for Edges {
+    /// Extracts `Edges` from a tuple of the rows of horizontal edges and the
+    /// rows of vertical edges.
+    fn extract(object: &'a PyObjectRef) -> PyResult<Self> {
+        let (horizontal, vertical) =
+            <(Vec<Vec<Edge>>, Vec<Vec<Edge>>)>::extract(object)?;
+
+        // There is one more row of horizontal edges than there are rows of
+        // vertical edges, while each row of vertical edges contains one more
+        // edge than the rows of horizontal edges.
+        let width = horizontal.first().map_or(0, Vec::len);
+        let is_valid = width > 0
+            && horizontal.len() == vertical.len() + 1
+            && horizontal.iter().all(|row| row.len() == width)
+            && vertical.iter().all(|row| row.len() == width + 1);
+
+        if !is_valid {
+            return Err(exc::ValueError::new("edges do not form a grid"));
+        }
+
+        Ok(Self {
+            horizontal,
+            vertical,
+        })
+    }
+}
+
+impl IntoPyObject for Edges {
+    /// Converts the edges into a tuple of the rows of horizontal edges and the
+    /// rows of vertical edges, in the same format that is accepted as edges.
+    fn into_object(self, py: Python) -> PyObject {
+        let to_strings = |rows: Vec<Vec<Edge>>, direction| {
+            rows.into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|edge| edge.to_ascii(direction).to_string())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let horizontal = to_strings(self.horizontal, Horizontal);
+        let vertical = to_strings(self.vertical, Vertical);
+
+        (horizontal, vertical).into_object(py)
+    }
+}
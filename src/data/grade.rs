@@ -0,0 +1,53 @@
+use pyo3::prelude::*;
+
+use data::Difficulty;
+
+/// The result of grading a Suriza puzzle, containing how often the rules of
+/// each tier were applied to solve it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grade {
+    /// Number of edges found by counting the lines around cells and
+    /// intersections.
+    pub basic_count: usize,
+    /// Number of edges found by constraints at the corner of a cell or an
+    /// intersection.
+    pub corner_count: usize,
+    /// Number of edges found by constraints passed on diagonally across cells.
+    pub diagonal_count: usize,
+    /// Number of edges found by avoiding closed sub-loops.
+    pub loop_count: usize,
+    /// The highest number of nested guesses that were necessary to find the
+    /// solution.
+    pub backtracking_depth: usize,
+    /// A score that grows with the number of edges found by harder rules,
+    /// which allows ordering puzzles of the same `Difficulty`.
+    pub score: usize,
+    pub difficulty: Difficulty,
+}
+
+impl IntoPyObject for Grade {
+    /// Converts the grade into a `dict` containing each of its fields, with
+    /// the difficulty given by its name.
+    fn into_object(self, py: Python) -> PyObject {
+        let dict = PyDict::new(py);
+
+        let items = [
+            ("basic_count", self.basic_count),
+            ("corner_count", self.corner_count),
+            ("diagonal_count", self.diagonal_count),
+            ("loop_count", self.loop_count),
+            ("backtracking_depth", self.backtracking_depth),
+            ("score", self.score),
+        ];
+
+        // Setting items only fails for keys that can not be hashed.
+        for &(key, value) in &items {
+            dict.set_item(key, value).unwrap();
+        }
+
+        dict.set_item("difficulty", self.difficulty.get_name())
+            .unwrap();
+
+        dict.to_object(py)
+    }
+}
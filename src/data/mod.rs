@@ -1,23 +1,35 @@
+mod ascii_grid;
+mod board;
 mod cell;
 mod cell_index;
 mod cells;
 mod constraint;
+mod contradiction;
 mod corner_direction;
+mod difficulty;
 mod direction;
 mod edge;
 mod edge_direction;
 mod edge_index;
 mod edges;
+mod grade;
 mod horizontal_direction;
 mod intersection_index;
+mod parse_error;
+mod puzzle_error;
+mod rule;
 mod size;
+mod step;
 mod vertical_direction;
 
 pub use self::{
-    cell::Cell, cell_index::CellIndex, cells::Cells, constraint::Constraint,
-    corner_direction::CornerDirection, direction::Direction, edge::Edge,
-    edge_direction::EdgeDirection, edge_index::EdgeIndex, edges::Edges,
+    ascii_grid::AsciiGrid, board::Board, cell::Cell, cell_index::CellIndex,
+    cells::Cells, constraint::Constraint, contradiction::Contradiction,
+    corner_direction::CornerDirection, difficulty::Difficulty,
+    direction::Direction, edge::Edge, edge_direction::EdgeDirection,
+    edge_index::EdgeIndex, edges::Edges, grade::Grade,
     horizontal_direction::HorizontalDirection,
-    intersection_index::IntersectionIndex, size::Size,
+    intersection_index::IntersectionIndex, parse_error::ParseError,
+    puzzle_error::PuzzleError, rule::Rule, size::Size, step::Step,
     vertical_direction::VerticalDirection,
 };
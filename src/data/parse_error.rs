@@ -0,0 +1,69 @@
+use std::{error::Error, fmt};
+
+use pyo3::{exc::ValueError, PyErr};
+
+use self::ParseError::*;
+
+/// `Enum` that describes why a text could not be read as a Suriza puzzle in the
+/// ASCII format. Lines and columns are counted from one, so they can be looked
+/// up in a text editor.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseError {
+    /// The text does not contain a grid with at least one cell.
+    Empty,
+    /// The character is not allowed at its position in the grid.
+    UnexpectedCharacter {
+        line: usize,
+        column: usize,
+        found: char,
+        expected: &'static str,
+    },
+    /// The line ends before all of the characters it requires were given.
+    UnexpectedEndOfLine {
+        line: usize,
+        column: usize,
+        expected: &'static str,
+    },
+    /// The grid ends with a line of cells instead of a line of intersections.
+    MissingLine { line: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Empty => write!(formatter, "input does not contain a grid"),
+            UnexpectedCharacter {
+                line,
+                column,
+                found,
+                expected,
+            } => write!(
+                formatter,
+                "line {}, column {}: unexpected character {:?}, expected {}",
+                line, column, found, expected
+            ),
+            UnexpectedEndOfLine {
+                line,
+                column,
+                expected,
+            } => write!(
+                formatter,
+                "line {}, column {}: unexpected end of line, expected {}",
+                line, column, expected
+            ),
+            MissingLine { line } => write!(
+                formatter,
+                "line {}: expected a line of intersections to close the grid",
+                line
+            ),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<ParseError> for PyErr {
+    fn from(error: ParseError) -> Self {
+        ValueError::new(error.to_string())
+    }
+}
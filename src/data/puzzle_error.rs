@@ -0,0 +1,60 @@
+use std::{error::Error, fmt};
+
+use pyo3::{exc::ValueError, PyErr};
+
+use self::PuzzleError::*;
+
+/// `Enum` that describes why the given rows of cells do not form a valid Suriza
+/// puzzle. Rows and columns are counted from zero, like the indices of cells.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PuzzleError {
+    /// The puzzle does not contain a single cell.
+    Empty,
+    /// The row contains a different number of cells than the first row.
+    RaggedRow {
+        row: usize,
+        length: usize,
+        expected: usize,
+    },
+    /// The value of the cell is neither a number from 0 to 3 nor blank.
+    InvalidCell {
+        row: usize,
+        column: usize,
+        value: String,
+    },
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Empty => write!(formatter, "puzzle does not contain any cells"),
+            RaggedRow {
+                row,
+                length,
+                expected,
+            } => write!(
+                formatter,
+                "row {} contains {} cells, expected {} like the first row",
+                row, length, expected
+            ),
+            InvalidCell {
+                row,
+                column,
+                ref value,
+            } => write!(
+                formatter,
+                "cell at row {}, column {} contains {:?}, expected a number \
+                 from 0 to 3 or ' '",
+                row, column, value
+            ),
+        }
+    }
+}
+
+impl Error for PuzzleError {}
+
+impl From<PuzzleError> for PyErr {
+    fn from(error: PuzzleError) -> Self {
+        ValueError::new(error.to_string())
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt;
+
+use data::{CellIndex, Constraint, IntersectionIndex};
+
+use self::Rule::*;
+
+/// `Enum` that describes the reasoning which determined the value of an edge
+/// while solving a Suriza puzzle.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Rule {
+    /// The cell is either surrounded by as many lines as its number requires,
+    /// or by so many x's that all of its other edges have to be lines.
+    CellCount(CellIndex),
+    /// Two lines already meet at the intersection, so none of its other edges
+    /// can be a line.
+    IntersectionDegree(IntersectionIndex),
+    /// The number in the cell and the two edges at one of its corners create a
+    /// constraint for the two edges at the opposite corner.
+    CellCorner {
+        cell: CellIndex,
+        constraint: Constraint,
+    },
+    /// Two edges at the intersection create a constraint for the two edges at
+    /// the opposite corner.
+    IntersectionCorner {
+        intersection: IntersectionIndex,
+        constraint: Constraint,
+    },
+    /// The constraint created by the `origin` was passed on diagonally through
+    /// the `cells` before it determined the value of the edge.
+    DiagonalConstraint {
+        origin: Box<Rule>,
+        cells: Vec<CellIndex>,
+    },
+    /// A line at the edge would close a loop that does not contain all of the
+    /// other lines.
+    LoopAvoidance,
+    /// None of the other rules applied, so the value was guessed. The guess
+    /// turned out to be correct.
+    Guess,
+}
+
+impl Rule {
+    /// Returns a short identifier for the kind of the rule.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            CellCount(_) => "cell_count",
+            IntersectionDegree(_) => "intersection_degree",
+            CellCorner { .. } => "cell_corner",
+            IntersectionCorner { .. } => "intersection_corner",
+            DiagonalConstraint { .. } => "diagonal_constraint",
+            LoopAvoidance => "loop_avoidance",
+            Guess => "guess",
+        }
+    }
+
+    /// Returns how hard it is to spot this rule when solving a puzzle by hand.
+    /// Lower values are easier.
+    pub fn get_difficulty(&self) -> usize {
+        match self {
+            CellCount(_) | IntersectionDegree(_) => 0,
+            CellCorner { .. } | IntersectionCorner { .. } => 1,
+            DiagonalConstraint { .. } => 2,
+            LoopAvoidance => 3,
+            Guess => 4,
+        }
+    }
+}
+
+fn describe_constraint(constraint: Constraint) -> &'static str {
+    match constraint {
+        Constraint::Line => "exactly one is a line",
+        Constraint::NoLine => "either both or none are lines",
+        Constraint::NoCorner => "at most one is a line",
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CellCount(CellIndex { row, column }) => write!(
+                formatter,
+                "the lines around the cell at row {}, column {} are already \
+                 known",
+                row, column
+            ),
+            IntersectionDegree(IntersectionIndex { row, column }) => write!(
+                formatter,
+                "two lines already meet at the intersection at row {}, column \
+                 {}",
+                row, column
+            ),
+            CellCorner {
+                cell: CellIndex { row, column },
+                constraint,
+            } => write!(
+                formatter,
+                "the number in the cell at row {}, column {} and two of its \
+                 edges require that of the two edges diagonally across from \
+                 them {}",
+                row,
+                column,
+                describe_constraint(*constraint)
+            ),
+            IntersectionCorner {
+                intersection: IntersectionIndex { row, column },
+                constraint,
+            } => write!(
+                formatter,
+                "two edges at the intersection at row {}, column {} require \
+                 that of its other two edges {}",
+                row,
+                column,
+                describe_constraint(*constraint)
+            ),
+            DiagonalConstraint { origin, cells } => {
+                write!(formatter, "{}, which was passed on through", origin)?;
+
+                for (position, CellIndex { row, column }) in
+                    cells.iter().enumerate()
+                {
+                    let separator = if position == 0 { "" } else { " and" };
+                    write!(
+                        formatter,
+                        "{} the cell at row {}, column {}",
+                        separator, row, column
+                    )?;
+                }
+
+                Ok(())
+            }
+            LoopAvoidance => write!(
+                formatter,
+                "a line would close a loop that is not a complete solution"
+            ),
+            Guess => write!(
+                formatter,
+                "it was guessed after none of the other rules applied"
+            ),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::fmt;
+
+use pyo3::prelude::*;
+
+use data::{Edge, EdgeDirection, EdgeIndex, Rule};
+
+/// A single step taken while solving a Suriza puzzle, consisting of the value
+/// that was assigned to an edge and the `Rule` that determined it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub edge: EdgeIndex,
+    pub value: Edge,
+    pub rule: Rule,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let EdgeIndex {
+            direction,
+            row,
+            column,
+        } = self.edge;
+
+        let direction = match direction {
+            EdgeDirection::Horizontal => "horizontal",
+            EdgeDirection::Vertical => "vertical",
+        };
+
+        let value = match self.value {
+            Edge::Line => "a line",
+            Edge::X => "an x",
+            Edge::Unknown => "unknown",
+        };
+
+        write!(
+            formatter,
+            "The {} edge at row {}, column {} is {}, because {}.",
+            direction, row, column, value, self.rule
+        )
+    }
+}
+
+impl IntoPyObject for Step {
+    /// Converts the step into a tuple of the rule name, the edge as a tuple of
+    /// its direction, row and column, the value of the edge as a character and
+    /// a readable explanation.
+    fn into_object(self, py: Python) -> PyObject {
+        let EdgeIndex {
+            direction,
+            row,
+            column,
+        } = self.edge;
+
+        let direction = match direction {
+            EdgeDirection::Horizontal => "horizontal",
+            EdgeDirection::Vertical => "vertical",
+        };
+
+        let value = match self.value {
+            Edge::Line => "-",
+            Edge::X => "x",
+            Edge::Unknown => " ",
+        };
+
+        let explanation = self.to_string();
+
+        (
+            self.rule.get_name(),
+            (direction, row, column),
+            value,
+            explanation,
+        )
+            .into_object(py)
+    }
+}
@@ -0,0 +1,390 @@
+//! Generation of random Suriza puzzles with a unique solution
+
+use algorithm;
+use data::{
+    Cell, CellIndex, Cells, Difficulty, Edge, EdgeDirection, EdgeIndex, Edges,
+    Size,
+};
+use grading;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: Size = Size {
+        width: 5,
+        height: 5,
+    };
+
+    #[test]
+    fn generates_same_puzzle_for_same_seed() {
+        assert_eq!(
+            generate(&SIZE, Difficulty::Hard, 42).unwrap(),
+            generate(&SIZE, Difficulty::Hard, 42).unwrap()
+        );
+    }
+
+    #[test]
+    fn generates_different_puzzles_for_different_seeds() {
+        assert_ne!(
+            generate(&SIZE, Difficulty::Hard, 1).unwrap(),
+            generate(&SIZE, Difficulty::Hard, 2).unwrap()
+        );
+    }
+
+    #[test]
+    fn generates_puzzles_with_unique_solution() {
+        for seed in 0..5 {
+            let cells = generate(&SIZE, Difficulty::Hard, seed).unwrap();
+
+            assert_eq!(cells.get_size(), SIZE);
+            assert!(algorithm::has_unique_solution(&cells));
+        }
+    }
+
+    #[test]
+    fn generates_puzzles_no_harder_than_requested() {
+        for seed in 0..5 {
+            let cells = generate(&SIZE, Difficulty::Medium, seed).unwrap();
+            let grade = grading::grade(&cells).unwrap();
+
+            assert!(grade.difficulty <= Difficulty::Medium);
+        }
+    }
+
+    // The only loop in a single cell can not be shown by a number, so the
+    // puzzle always has the empty loop as a second solution.
+    #[test]
+    fn gives_up_on_sizes_without_unique_puzzle() {
+        let size = Size {
+            width: 1,
+            height: 1,
+        };
+
+        assert_eq!(generate(&size, Difficulty::Easy, 0), None);
+    }
+
+    #[test]
+    fn creates_single_loop() {
+        let mut random = Random::new(7);
+        let edges = create_loop(&SIZE, &mut random);
+
+        assert!(is_single_loop(&edges));
+    }
+}
+
+/// The number of loops that are tried in order to find a puzzle of the
+/// requested difficulty.
+const ATTEMPTS: usize = 16;
+
+/// The number of loops that are tried before giving up if none of them leads
+/// to a puzzle with a unique solution.
+const MAX_ATTEMPTS: usize = 256;
+
+/// A small xorshift pseudo random number generator. Generating puzzles has to
+/// be reproducible from the seed alone, so the sequence of numbers must not
+/// depend on anything but the seed.
+struct Random {
+    state: u64,
+}
+
+impl Random {
+    fn new(seed: u64) -> Random {
+        // A state of zero would only ever produce zeros, so the seed is mixed
+        // with a constant that is not zero for any of the small seeds that are
+        // most likely to be used.
+        let state =
+            (seed ^ 0x9E37_79B9_7F4A_7C15).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+
+        Random {
+            state: if state == 0 { 1 } else { state },
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Returns a number in the range `0..bound`.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Returns the indices of the cells that share an edge with the given cell.
+fn index_neighbours(
+    &Size { width, height }: &Size,
+    &CellIndex { row, column }: &CellIndex,
+) -> Vec<CellIndex> {
+    let mut neighbours = Vec::new();
+
+    if row > 0 {
+        neighbours.push(CellIndex {
+            row: row - 1,
+            column,
+        });
+    }
+    if row + 1 < height {
+        neighbours.push(CellIndex {
+            row: row + 1,
+            column,
+        });
+    }
+    if column > 0 {
+        neighbours.push(CellIndex {
+            row,
+            column: column - 1,
+        });
+    }
+    if column + 1 < width {
+        neighbours.push(CellIndex {
+            row,
+            column: column + 1,
+        });
+    }
+
+    neighbours
+}
+
+/// Creates the edges surrounding a region of cells. An edge is a line if it
+/// separates a cell inside the region from one outside of it, where everything
+/// beyond the border of the grid counts as outside.
+fn surround_region(size: &Size, region: &[Vec<bool>]) -> Edges {
+    let mut edges = Edges::create_empty(size);
+    let is_inside = |row: Option<usize>, column: Option<usize>| {
+        let row = row.and_then(|row| region.get(row));
+        row.and_then(|row| row.get(column?))
+            .map_or(false, |&value| value)
+    };
+
+    for index in edges.index_edges() {
+        let EdgeIndex {
+            row,
+            column,
+            direction,
+        } = index;
+
+        // A horizontal edge lies between the cell above and the cell below it,
+        // a vertical edge between the cell to its left and the one to its
+        // right.
+        let (first, second) = match direction {
+            EdgeDirection::Horizontal => (
+                is_inside(row.checked_sub(1), column.into()),
+                is_inside(row.into(), column.into()),
+            ),
+            EdgeDirection::Vertical => (
+                is_inside(row.into(), column.checked_sub(1)),
+                is_inside(row.into(), column.into()),
+            ),
+        };
+
+        edges[index] = if first != second { Edge::Line } else { Edge::X };
+    }
+
+    edges
+}
+
+/// Returns whether all lines of the given edges form one closed loop.
+fn is_single_loop(edges: &Edges) -> bool {
+    let line_count = edges
+        .index_edges()
+        .filter(|&index| edges[index].is_line())
+        .count();
+
+    let has_branches = edges.index_intersections().any(|index| {
+        let lines = edges
+            .index_adjacent_edges(index)
+            .flatten()
+            .filter(|&edge_index| edges[edge_index].is_line())
+            .count();
+        lines > 2
+    });
+
+    let start = edges.index_intersections().find(|&index| {
+        edges
+            .index_adjacent_edges(index)
+            .flatten()
+            .any(|edge_index| edges[edge_index].is_line())
+    });
+
+    !has_branches
+        && start.and_then(|start| algorithm::get_loop_length(edges, start))
+            == line_count.into()
+}
+
+/// Creates a random loop by growing a region of cells one neighbouring cell at
+/// a time, as long as the border of the region stays a single loop.
+fn create_loop(size: &Size, random: &mut Random) -> Edges {
+    let Size { width, height } = *size;
+    let mut region = vec![vec![false; width]; height];
+
+    let start = CellIndex {
+        row: random.below(height),
+        column: random.below(width),
+    };
+    region[start.row][start.column] = true;
+
+    // Growing the region to roughly half of the grid leaves room for the loop
+    // to wind through the whole puzzle.
+    let target = (width * height + 1) / 2;
+    let mut region_size = 1;
+
+    while region_size < target {
+        let mut candidates: Vec<_> = iproduct!(0..height, 0..width)
+            .map(|(row, column)| CellIndex { row, column })
+            .filter(|index| !region[index.row][index.column])
+            .filter(|index| {
+                index_neighbours(size, index)
+                    .iter()
+                    .any(|neighbour| region[neighbour.row][neighbour.column])
+            })
+            .collect();
+        random.shuffle(&mut candidates);
+
+        let grown = candidates.into_iter().find(|index| {
+            region[index.row][index.column] = true;
+            let is_valid = is_single_loop(&surround_region(size, &region));
+            region[index.row][index.column] = false;
+            is_valid
+        });
+
+        match grown {
+            Some(index) => region[index.row][index.column] = true,
+            None => break,
+        }
+
+        region_size += 1;
+    }
+
+    surround_region(size, &region)
+}
+
+/// Creates a puzzle showing the number of lines around every cell.
+fn create_clues(size: &Size, edges: &Edges) -> Cells {
+    let mut cells = Cells::create_empty(size);
+
+    for index in cells.index_cells() {
+        let line_count = index
+            .index_edges()
+            .filter(|&edge_index| edges[edge_index].is_line())
+            .count();
+
+        // Only a loop around a single cell in a grid of size one surrounds a
+        // cell with four lines, which no number can express.
+        cells[&index] = match line_count {
+            0 => Cell::Zero,
+            1 => Cell::One,
+            2 => Cell::Two,
+            3 => Cell::Three,
+            _ => Cell::Any,
+        };
+    }
+
+    cells
+}
+
+/// Removes numbers from the puzzle in random order, as long as the puzzle keeps
+/// a unique solution and does not get harder than the given `difficulty`.
+fn remove_clues(
+    cells: &mut Cells,
+    difficulty: Difficulty,
+    random: &mut Random,
+) {
+    let mut indices: Vec<_> = cells.index_cells().collect();
+    random.shuffle(&mut indices);
+
+    for index in indices {
+        let clue = cells[&index];
+        cells[&index] = Cell::Any;
+
+        let is_valid = algorithm::has_unique_solution(cells)
+            && grading::grade(cells)
+                .map_or(false, |grade| grade.difficulty <= difficulty);
+
+        if !is_valid {
+            cells[&index] = clue;
+        }
+    }
+}
+
+/// Returns how many tiers of difficulty lie between `a` and `b`.
+fn get_distance(a: Difficulty, b: Difficulty) -> usize {
+    (a as isize - b as isize).abs() as usize
+}
+
+/// Generates a random Suriza puzzle of the given `Size` with a unique solution.
+/// The same `seed` always leads to the same puzzle, so a puzzle can be
+/// recreated from its size, difficulty and seed.
+///
+/// If none of the attempted puzzles has exactly the requested `difficulty`,
+/// the one closest to it is returned instead, preferring easier puzzles.
+/// Returns `None` if no puzzle with a unique solution is found, which is the
+/// case for a grid of a single cell.
+///
+/// # Panics
+///
+/// Panics if either the `width` or `height` of the given `Size` are equal to
+/// zero.
+pub fn generate(
+    size: &Size,
+    difficulty: Difficulty,
+    seed: u64,
+) -> Option<Cells> {
+    let mut random = Random::new(seed);
+    let mut best: Option<(Difficulty, Cells)> = None;
+
+    for attempt in 0..MAX_ATTEMPTS {
+        if attempt >= ATTEMPTS && best.is_some() {
+            break;
+        }
+
+        let edges = create_loop(size, &mut random);
+        let mut cells = create_clues(size, &edges);
+
+        // Even with every number shown, a loop might not be the only one that
+        // matches them.
+        if !algorithm::has_unique_solution(&cells) {
+            continue;
+        }
+
+        // Removing numbers never makes a puzzle easier, so a puzzle that
+        // already is too hard is kept as hard as it is.
+        let initial = grading::grade(&cells).unwrap().difficulty;
+        let limit = if initial > difficulty {
+            initial
+        } else {
+            difficulty
+        };
+
+        remove_clues(&mut cells, limit, &mut random);
+
+        let reached = grading::grade(&cells).unwrap().difficulty;
+        if reached == difficulty {
+            return cells.into();
+        }
+
+        let is_better = best.as_ref().map_or(true, |&(best_difficulty, _)| {
+            let distance = get_distance(reached, difficulty);
+            let best_distance = get_distance(best_difficulty, difficulty);
+
+            distance < best_distance
+                || distance == best_distance && reached < best_difficulty
+        });
+
+        if is_better {
+            best = (reached, cells).into();
+        }
+    }
+
+    best.map(|(_, cells)| cells)
+}
@@ -0,0 +1,164 @@
+//! Grading of Suriza puzzles by the rules that are required to solve them
+
+use algorithm;
+use data::{Cells, Contradiction, Difficulty, Edges, Grade, Rule, Step};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grades_puzzle_solved_by_counting_as_easy() {
+        let cells = Cells::from_ascii(
+            "
+            + + + + +
+             2 1 2 3
+            + + + + +
+             1
+            + + + + +
+               2 0
+            + + + + +
+               1 0
+            + + + + +
+        ",
+        );
+        let grade = grade(&cells).unwrap();
+
+        assert_eq!(grade.difficulty, Difficulty::Easy);
+        assert_eq!(grade.basic_count + grade.corner_count, 40);
+        assert_eq!(grade.score, grade.corner_count);
+    }
+
+    #[test]
+    fn grades_puzzle_requiring_diagonal_constraints_as_medium() {
+        let cells = Cells::from_ascii(
+            "
+            + + + + +
+                 2
+            + + + + +
+             3   1 2
+            + + + + +
+               1 2 2
+            + + + + +
+               0   3
+            + + + + +
+        ",
+        );
+        let grade = grade(&cells).unwrap();
+
+        assert_eq!(grade.difficulty, Difficulty::Medium);
+        assert!(grade.diagonal_count > 0);
+        assert_eq!(grade.backtracking_depth, 0);
+    }
+
+    #[test]
+    fn grades_puzzle_requiring_guesses_as_hard() {
+        let cells = Cells::from_ascii(
+            "
+            + + + + + + + +
+             3       1   2
+            + + + + + + + +
+               2   1   1 3
+            + + + + + + + +
+             3 0 2 2     2
+            + + + + + + + +
+                     1 2 3
+            + + + + + + + +
+             3           2
+            + + + + + + + +
+                     2
+            + + + + + + + +
+             3     3 1   3
+            + + + + + + + +
+        ",
+        );
+        let grade = grade(&cells).unwrap();
+
+        assert_eq!(grade.difficulty, Difficulty::Hard);
+        assert!(grade.backtracking_depth > 0);
+    }
+
+    #[test]
+    fn does_not_grade_puzzle_without_solution() {
+        let cells = Cells::from_ascii(
+            "
+            + +
+             0
+            + +
+             3
+            + +
+        ",
+        );
+
+        assert!(grade(&cells).is_err());
+    }
+}
+
+/// Solves the given Suriza puzzle and grades it by how often the rules of each
+/// tier were needed to do so. Like for a hint, each step is counted by the
+/// easiest rule that is able to take it, so the grade does not depend on the
+/// order in which the solver tries its rules. Returns a `Contradiction` if the
+/// puzzle has no solution.
+pub fn grade(cells: &Cells) -> Result<Grade, Contradiction> {
+    let mut grade = Grade {
+        basic_count: 0,
+        corner_count: 0,
+        diagonal_count: 0,
+        loop_count: 0,
+        backtracking_depth: 0,
+        score: 0,
+        difficulty: Difficulty::Easy,
+    };
+
+    let mut edges = {
+        let size = cells.get_size();
+        Edges::create_empty(&size)
+    };
+
+    while let Some(step) = algorithm::hint(cells, &edges)? {
+        add_step(&mut grade, &step);
+        edges[step.edge] = step.value;
+    }
+
+    // Once the rules are stuck, the rest of the puzzle can only be solved by
+    // guessing. Only the guesses leading to the solution are counted, each of
+    // which was made after the ones before it.
+    if edges.index_edges().any(|index| edges[index].is_unknown()) {
+        let (_, trace) = algorithm::solve_with_trace(cells)?;
+
+        for step in &trace {
+            if let Rule::Guess = step.rule {
+                add_step(&mut grade, step);
+            }
+        }
+    }
+
+    grade.difficulty = if grade.backtracking_depth > 0 {
+        Difficulty::Hard
+    } else if grade.diagonal_count > 0 || grade.loop_count > 0 {
+        Difficulty::Medium
+    } else {
+        Difficulty::Easy
+    };
+
+    Ok(grade)
+}
+
+/// Counts the `step` for the tier of its rule and adds to the score.
+fn add_step(grade: &mut Grade, step: &Step) {
+    match step.rule {
+        Rule::CellCount(_) | Rule::IntersectionDegree(_) => {
+            grade.basic_count += 1
+        }
+        Rule::CellCorner { .. } | Rule::IntersectionCorner { .. } => {
+            grade.corner_count += 1
+        }
+        Rule::DiagonalConstraint { .. } => grade.diagonal_count += 1,
+        Rule::LoopAvoidance => grade.loop_count += 1,
+        Rule::Guess => grade.backtracking_depth += 1,
+    }
+
+    // Harder rules add more to the score, while counting lines around cells
+    // and intersections does not add anything.
+    grade.score += step.rule.get_difficulty();
+}
@@ -0,0 +1,38 @@
+use crate::std::time::Duration;
+
+use super::Units;
+
+/// Describes how to connect to a GRBL and how to prepare it for plotting once
+/// it has started.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    pub baud_rate: u32,
+    /// How long to wait for a response. The GRBL will not respond until the
+    /// command is executed, which can take a while, for example when it is
+    /// moving to the home position.
+    pub timeout: Duration,
+    /// Whether the GRBL is unlocked by homing the machine with '$H' instead of
+    /// clearing the lock with '$X', which requires homing switches.
+    pub homing: bool,
+    pub units: Units,
+    /// The feed rate of linear motions in units per minute.
+    pub feed_rate: f64,
+    /// Additional G-code commands that are sent after the GRBL is set up.
+    pub startup: Vec<String>,
+}
+
+impl Default for Config {
+    /// Returns the configuration of the original machine, which has homing
+    /// switches and communicates at the baud rate given in the official GRBL
+    /// documentation.
+    fn default() -> Self {
+        Config {
+            baud_rate: 115_200,
+            timeout: Duration::from_secs(30),
+            homing: true,
+            units: Units::Millimeters,
+            feed_rate: 2000.0,
+            startup: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{
+    grbl::MachineState,
+    pyo3::{exc, PyErr},
+    std::{error, fmt, io},
+};
+
+use self::Error::*;
+
+py_exception!(libsuriza, GRBLError, exc::Exception);
+py_exception!(libsuriza, GRBLCommandError, GRBLError);
+py_exception!(libsuriza, GRBLAlarm, GRBLError);
+
+/// `Enum` that describes why communicating with the GRBL failed.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading from or writing to the port failed, e.g. due to a timeout.
+    Io(io::Error),
+    /// The GRBL rejected the command in the given line with the error code.
+    Command { code: u8, line: String },
+    /// The GRBL entered an alarm state for the reason given by the code.
+    Alarm(u8),
+    /// A status report showed that the GRBL is in an alarm state. The reason
+    /// for the alarm is only reported once, when it occurs.
+    AlarmState,
+    /// A status report showed that the machine is held or waiting for its
+    /// door to be closed, so it does not continue on its own.
+    HaltedState(MachineState),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Io(ref error) => write!(formatter, "{}", error),
+            Command { code, ref line } => write!(
+                formatter,
+                "error {} in {:?}: {}",
+                code,
+                line,
+                get_error_message(code)
+            ),
+            Alarm(code) => {
+                write!(formatter, "alarm {}: {}", code, get_alarm_message(code))
+            }
+            AlarmState => write!(formatter, "machine is in an alarm state"),
+            HaltedState(state) => write!(
+                formatter,
+                "machine is halted in the {} state",
+                state.get_name()
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Io(error)
+    }
+}
+
+impl From<Error> for PyErr {
+    /// Converts errors reported by the GRBL into a `GRBLCommandError` or a
+    /// `GRBLAlarm`, which carry the code and its description, and in case of a
+    /// `GRBLCommandError` the rejected command. A halted machine is reported
+    /// as a `GRBLError`. Errors of the port are converted into an `IOError`.
+    fn from(error: Error) -> Self {
+        let message = error.to_string();
+
+        match error {
+            Io(error) => error.into(),
+            Command { code, line } => {
+                GRBLCommandError::new((code, message, line))
+            }
+            Alarm(code) => GRBLAlarm::new((code, message)),
+            AlarmState => GRBLAlarm::new((None::<u8>, message)),
+            HaltedState(_) => GRBLError::new(message),
+        }
+    }
+}
+
+/// Returns the description of an error code as given in the documentation of
+/// GRBL 1.1.
+fn get_error_message(code: u8) -> &'static str {
+    match code {
+        1 => "expected command letter",
+        2 => "bad number format",
+        3 => "invalid '$' system command",
+        4 => "negative value",
+        5 => "homing cycle is not enabled",
+        6 => "minimum step pulse time must be greater than 3 microseconds",
+        7 => "EEPROM read failed, using defaults",
+        8 => "'$' command is only valid when idle",
+        9 => "G-code is locked out during alarm or jog state",
+        10 => "soft limits can not be enabled without homing",
+        11 => "maximum line length exceeded",
+        12 => "step rate exceeds 30kHz",
+        13 => "safety door detected as opened",
+        14 => "build info or startup line exceeds EEPROM line length",
+        15 => "jog target exceeds machine travel",
+        16 => "invalid jog command",
+        17 => "laser mode requires PWM output",
+        20 => "unsupported or invalid G-code command",
+        21 => "more than one G-code command from the same modal group",
+        22 => "feed rate has not yet been set or is undefined",
+        23 => "G-code command requires an integer value",
+        24 => "more than one G-code command requires axis words",
+        25 => "repeated G-code word",
+        26 => "no axis words found in command",
+        27 => "line number value is invalid",
+        28 => "G-code command is missing a required value word",
+        29 => "G59.x work coordinate systems are not supported",
+        30 => "G53 is only allowed with G0 and G1 motion modes",
+        31 => "axis words found in command that does not use them",
+        32 => "G2 and G3 arcs require at least one in-plane axis word",
+        33 => "motion command target is invalid",
+        34 => "arc radius value is invalid",
+        35 => "G2 and G3 arcs require at least one in-plane offset word",
+        36 => "unused value words found in command",
+        37 => "G43.1 tool length offset is not assigned to configured axis",
+        38 => "tool number greater than maximum supported value",
+        _ => "unknown error",
+    }
+}
+
+/// Returns the description of an alarm code as given in the documentation of
+/// GRBL 1.1.
+fn get_alarm_message(code: u8) -> &'static str {
+    match code {
+        1 => "hard limit triggered, position is likely lost",
+        2 => "motion target exceeds machine travel",
+        3 => "reset while in motion, position is likely lost",
+        4 => "probe is not in the expected initial state",
+        5 => "probe did not contact the workpiece",
+        6 => "homing cycle was reset",
+        7 => "safety door was opened during homing cycle",
+        8 => "homing cycle failed to clear limit switch",
+        9 => "homing cycle could not find limit switch",
+        _ => "unknown alarm",
+    }
+}
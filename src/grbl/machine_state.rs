@@ -0,0 +1,52 @@
+use self::MachineState::*;
+
+/// `Enum` that represents the state a GRBL reports in its status reports.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MachineState {
+    Idle,
+    Run,
+    Hold,
+    Jog,
+    Alarm,
+    Door,
+    Check,
+    Home,
+    Sleep,
+}
+
+impl MachineState {
+    /// Parses the state as it appears at the start of a status report. Some
+    /// states are followed by a sub-state, e.g. "Hold:0", which is ignored.
+    pub fn parse(value: &str) -> Option<MachineState> {
+        let name = value.split(':').next()?;
+
+        match name {
+            "Idle" => Idle,
+            "Run" => Run,
+            "Hold" => Hold,
+            "Jog" => Jog,
+            "Alarm" => Alarm,
+            "Door" => Door,
+            "Check" => Check,
+            "Home" => Home,
+            "Sleep" => Sleep,
+            _ => return None,
+        }
+        .into()
+    }
+
+    /// Returns the name of the state as it is reported by the GRBL.
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Idle => "Idle",
+            Run => "Run",
+            Hold => "Hold",
+            Jog => "Jog",
+            Alarm => "Alarm",
+            Door => "Door",
+            Check => "Check",
+            Home => "Home",
+            Sleep => "Sleep",
+        }
+    }
+}
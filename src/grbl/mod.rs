@@ -0,0 +1,916 @@
+//! Communication with a GRBL controller over a USB port
+
+extern crate serialport;
+
+mod config;
+mod error;
+mod machine_state;
+mod pen;
+mod plot_settings;
+mod program;
+mod response;
+mod setting_kind;
+mod setting_value;
+mod simulator;
+mod status;
+mod transform;
+mod transport;
+mod units;
+
+pub use self::{
+    config::Config,
+    error::{Error, GRBLAlarm, GRBLCommandError, GRBLError},
+    machine_state::MachineState,
+    pen::Pen,
+    plot_settings::PlotSettings,
+    program::Program,
+    response::Response,
+    setting_kind::SettingKind,
+    setting_value::SettingValue,
+    simulator::Simulator,
+    status::Status,
+    transform::Transform,
+    transport::Transport,
+    units::Units,
+};
+
+use crate::{
+    pyo3::prelude::*,
+    std::{
+        collections::{BTreeMap, VecDeque},
+        io,
+        io::prelude::*,
+        string::FromUtf8Error,
+        thread,
+        time::{Duration, Instant},
+    },
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connect() -> GRBL {
+        connect_with(Config::default())
+    }
+
+    fn connect_with(config: Config) -> GRBL {
+        let mut grbl = GRBL::new(Box::new(Simulator::new()), config);
+        let is_homing = grbl.config.homing;
+        grbl.exchange_init_sequence(is_homing).unwrap();
+        grbl
+    }
+
+    #[test]
+    fn initializes_simulated_grbl() {
+        let status = connect().query_status().unwrap();
+
+        assert_eq!(status.state, MachineState::Idle);
+        assert_eq!(status.machine_position, Some([0.0, 0.0, 0.0]));
+        assert_eq!(status.feed_rate, Some(2000.0));
+    }
+
+    #[test]
+    fn initializes_with_configuration() {
+        let mut grbl = connect_with(Config {
+            homing: false,
+            units: Units::Inches,
+            feed_rate: 50.0,
+            startup: vec!["G0 X1".to_string()],
+            ..Config::default()
+        });
+
+        let status = grbl.query_status().unwrap();
+        assert_eq!(status.machine_position, Some([25.4, 0.0, 0.0]));
+        assert_eq!(status.feed_rate, Some(50.0));
+    }
+
+    #[test]
+    fn moves_pen_on_z_axis() {
+        let mut grbl = connect();
+        grbl.pen = Pen::ZAxis {
+            up: 5.0,
+            down: -1.0,
+        };
+
+        grbl.send_commands(&grbl.pen.get_lower_commands()).unwrap();
+        let status = grbl.query_status().unwrap();
+        assert_eq!(status.machine_position, Some([0.0, 0.0, -1.0]));
+
+        grbl.send_commands(&grbl.pen.get_raise_commands()).unwrap();
+        let status = grbl.query_status().unwrap();
+        assert_eq!(status.machine_position, Some([0.0, 0.0, 5.0]));
+    }
+
+    #[test]
+    fn runs_program() {
+        let mut grbl = connect();
+        let edges = "+-+\n| |\n+-+".parse().unwrap();
+
+        let program = Program::from_edges(&edges, &PlotSettings::default());
+        grbl.run_program(&program).unwrap();
+
+        let status = grbl.query_status().unwrap();
+        assert_eq!(status.machine_position, Some([0.0, 0.0, 0.0]));
+        assert_eq!(status.spindle_speed, Some(0.0));
+    }
+
+    #[test]
+    fn streams_more_than_fits_into_receive_buffer() {
+        let mut grbl = connect();
+
+        for step in 1..=50 {
+            grbl.send(&format!("G1 X{} Y{}\n", step, 2 * step)).unwrap();
+
+            let in_flight: usize = grbl.in_flight.iter().map(String::len).sum();
+            assert!(in_flight <= RX_BUFFER_SIZE);
+        }
+        grbl.flush().unwrap();
+
+        let status = grbl.query_status().unwrap();
+        assert_eq!(status.machine_position, Some([50.0, 100.0, 0.0]));
+    }
+
+    #[test]
+    fn holds_and_resumes_motion() {
+        let mut grbl = connect();
+
+        grbl.write_realtime(FEED_HOLD).unwrap();
+        assert_eq!(grbl.query_status().unwrap().state, MachineState::Hold);
+
+        grbl.write_realtime(CYCLE_START).unwrap();
+        assert_eq!(grbl.query_status().unwrap().state, MachineState::Idle);
+    }
+
+    #[test]
+    fn does_not_wait_for_halted_machine() {
+        let mut grbl = GRBL::new(Box::new(Simulator::new()), Config::default());
+
+        match grbl.wait_idle() {
+            Err(Error::AlarmState) => (),
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        let mut grbl = connect();
+        grbl.write_realtime(FEED_HOLD).unwrap();
+        match grbl.wait_idle() {
+            Err(Error::HaltedState(MachineState::Hold)) => (),
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        grbl.write_realtime(CYCLE_START).unwrap();
+        grbl.wait_idle().unwrap();
+    }
+
+    #[test]
+    fn recovers_from_reset_while_streaming() {
+        let mut grbl = connect();
+
+        grbl.send("G1 X10\n").unwrap();
+        grbl.send("G5\n").unwrap();
+        grbl.send("G1 Y10\n").unwrap();
+        grbl.reset(false).unwrap();
+
+        assert!(grbl.in_flight.is_empty());
+        assert_eq!(grbl.query_status().unwrap().state, MachineState::Idle);
+        grbl.exchange("G1 X20\n").unwrap();
+    }
+
+    fn long_stream() -> Vec<String> {
+        (1..=50)
+            .map(|step| format!("G1 X{} Y{}", step, 2 * step))
+            .collect()
+    }
+
+    #[test]
+    fn holds_and_resumes_started_stream() {
+        let mut grbl = connect();
+
+        grbl.start(long_stream()).unwrap();
+        assert!(!grbl.queued.is_empty());
+
+        grbl.feed_hold().unwrap();
+        let status = grbl.status().unwrap();
+        assert_eq!(status.state, MachineState::Hold);
+        assert!(grbl.poll().unwrap() > 0);
+        assert_eq!(grbl.status().unwrap(), status);
+
+        grbl.resume().unwrap();
+        while grbl.poll().unwrap() > 0 {}
+
+        let status = grbl.status().unwrap();
+        assert_eq!(status.state, MachineState::Idle);
+        assert_eq!(status.machine_position, Some([50.0, 100.0, 0.0]));
+    }
+
+    #[test]
+    fn resets_while_stream_is_started() {
+        let mut grbl = connect();
+
+        grbl.start(long_stream()).unwrap();
+        grbl.poll().unwrap();
+        grbl.soft_reset(None).unwrap();
+
+        assert_eq!(grbl.poll().unwrap(), 0);
+        let status = grbl.status().unwrap();
+        assert_eq!(status.state, MachineState::Idle);
+        assert_ne!(status.machine_position, Some([50.0, 100.0, 0.0]));
+        assert_ne!(status.machine_position, Some([0.0, 0.0, 0.0]));
+
+        grbl.soft_reset(Some(true)).unwrap();
+        let status = grbl.status().unwrap();
+        assert_eq!(status.machine_position, Some([0.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn reads_and_changes_settings() {
+        let mut grbl = connect();
+
+        let settings = grbl.query_settings().unwrap();
+        assert_eq!(settings[&22], SettingValue::Boolean(true));
+        assert_eq!(settings[&100], SettingValue::Decimal(250.0));
+
+        grbl.exchange("$100=80\n").unwrap();
+        let settings = grbl.query_settings().unwrap();
+        assert_eq!(settings[&100], SettingValue::Decimal(80.0));
+    }
+
+    #[test]
+    fn reads_offsets_and_parser_state() {
+        let mut grbl = connect();
+
+        let offsets = grbl.query_offsets().unwrap();
+        assert_eq!(offsets["G54"], vec![0.0, 0.0, 0.0]);
+        assert_eq!(offsets["TLO"], vec![0.0]);
+        assert_eq!(offsets["PRB"], vec![0.0, 0.0, 0.0]);
+
+        let state = grbl.query_parser_state().unwrap();
+        assert!(state.contains(&"G21".to_string()));
+        assert!(state.contains(&"F2000".to_string()));
+    }
+
+    #[test]
+    fn reports_rejected_command() {
+        let mut grbl = connect();
+
+        match grbl.exchange("G5\n") {
+            Err(Error::Command { code, line }) => {
+                assert_eq!(code, 20);
+                assert_eq!(line, "G5");
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+}
+
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// Size of the buffer in which the GRBL receives messages. Streamed messages
+/// are sent as long as they fit into it, so the GRBL always has the next
+/// commands at hand.
+const RX_BUFFER_SIZE: usize = 128;
+
+/// Realtime commands, which are single bytes that are executed by the GRBL as
+/// soon as they are received, without being queued like other messages.
+const STATUS_QUERY: u8 = b'?';
+const FEED_HOLD: u8 = b'!';
+const CYCLE_START: u8 = b'~';
+const SOFT_RESET: u8 = 0x18;
+const JOG_CANCEL: u8 = 0x85;
+
+/// Milliseconds to wait between two status queries while waiting for the
+/// machine to become idle.
+const POLL_INTERVAL: u64 = 200;
+
+#[pyclass]
+pub struct GRBL {
+    port: Box<dyn Transport>,
+    config: Config,
+    pen: Pen,
+    buffer: [u8; BUFFER_SIZE],
+    pos: usize,
+    /// Messages that were sent, but not yet responded to, in the order in
+    /// which they were sent.
+    in_flight: VecDeque<String>,
+    /// Messages that wait to be sent until they fit into the receive buffer
+    /// of the GRBL, in the order in which they are sent.
+    queued: VecDeque<String>,
+    /// The first error the GRBL responded with while streaming, which is
+    /// reported by the next call that sends messages.
+    error: Option<Error>,
+}
+
+#[pymethods]
+impl GRBL {
+    /// Connects to the GRBL at the given `port`. Apart from the port, all of
+    /// the arguments are optional:
+    ///
+    /// - `baud_rate` defaults to 115200.
+    /// - `timeout` is the number of seconds to wait for a response, by default
+    ///   30.
+    /// - `homing` selects whether the machine is unlocked by homing it, which
+    ///   is the default, or by clearing the lock with '$X'.
+    /// - `units` is either "mm", the default, or "inch".
+    /// - `feed_rate` defaults to 2000 units per minute.
+    /// - `startup` is a list of additional G-code commands that are sent once
+    ///   the GRBL is set up, also after a soft reset.
+    #[new]
+    fn __new__(
+        obj: &PyRawObject,
+        port: String,
+        baud_rate: Option<u32>,
+        timeout: Option<f64>,
+        homing: Option<bool>,
+        units: Option<Units>,
+        feed_rate: Option<f64>,
+        startup: Option<Vec<String>>,
+    ) -> PyResult<()> {
+        if timeout.map_or(false, |timeout| !(timeout > 0.0)) {
+            return Err(exc::ValueError::new("timeout must be positive"));
+        }
+
+        if feed_rate.map_or(false, |feed_rate| !(feed_rate > 0.0)) {
+            return Err(exc::ValueError::new("feed rate must be positive"));
+        }
+
+        let default = Config::default();
+        let config = Config {
+            baud_rate: baud_rate.unwrap_or(default.baud_rate),
+            timeout: timeout.map_or(default.timeout, |timeout| {
+                Duration::from_millis((timeout * 1000.0) as u64)
+            }),
+            homing: homing.unwrap_or(default.homing),
+            units: units.unwrap_or(default.units),
+            feed_rate: feed_rate.unwrap_or(default.feed_rate),
+            startup: startup.unwrap_or(default.startup),
+        };
+
+        let mut handler = Self::try_connect(&port, config)?;
+        let is_homing = handler.config.homing;
+        handler.exchange_init_sequence(is_homing)?;
+
+        obj.init(move |_| handler)
+    }
+
+    /// Moves the pen to the specified position.
+    fn move_to(&mut self, x: f64, y: f64) -> PyResult<()> {
+        let mut program = Program::new();
+        program.move_to(x, y);
+
+        self.run_program(&program).map_err(Into::into)
+    }
+
+    /// Move the pen to the specified position at an increased rate.
+    fn seek_to(&mut self, x: f64, y: f64) -> PyResult<()> {
+        let mut program = Program::new();
+        program.seek_to(x, y);
+
+        self.run_program(&program).map_err(Into::into)
+    }
+
+    /// Lowers the pen, preparing it for drawing a line.
+    fn lower_pen(&mut self) -> PyResult<()> {
+        let mut program = Program::new();
+        program.lower_pen(&self.pen);
+
+        self.run_program(&program).map_err(Into::into)
+    }
+
+    /// Raises the pen so it can be moved across the plane without leaving a
+    /// line.
+    fn raise_pen(&mut self) -> PyResult<()> {
+        let mut program = Program::new();
+        program.raise_pen(&self.pen);
+
+        self.run_program(&program).map_err(Into::into)
+    }
+
+    /// Selects how the pen is lowered and raised, which is one of
+    ///
+    /// - `("servo", up, down, dwell)` for a servo driven by the PWM signal of
+    ///   the spindle. The positions are given as spindle speeds. After each
+    ///   move, the machine waits for `dwell` seconds until the servo has
+    ///   settled.
+    /// - `("servo", up, dwell)` for a servo that is lowered by turning off the
+    ///   spindle. The default is `("servo", 30, 0)` for the original machine.
+    /// - `("z-axis", up, down)` for a pen moved along the z-axis to the given
+    ///   heights.
+    /// - `("laser", power)` for a laser that is switched on with the given
+    ///   power, as a spindle speed, when the pen is lowered.
+    fn set_pen(&mut self, pen: Pen) -> PyResult<()> {
+        self.pen = pen;
+        Ok(())
+    }
+
+    /// Sends the given G-code commands without waiting for each of them to be
+    /// executed. As many commands are sent as fit into the receive buffer of
+    /// the GRBL, which allows it to plan a smooth motion across all of them.
+    /// Returns once every command was responded to.
+    ///
+    /// If the GRBL rejects a command, no further commands are sent and a
+    /// `GRBLCommandError` naming the command is raised. Commands that were
+    /// already sent are still executed.
+    ///
+    /// The machine can not be paused or reset until this returns, so `start`
+    /// and `poll` are better suited for long programs.
+    fn stream(&mut self, commands: Vec<String>) -> PyResult<()> {
+        self.send_commands(&commands).map_err(Into::into)
+    }
+
+    /// Streams all commands of the `program` in the same way as `stream`.
+    fn run(&mut self, program: &Program) -> PyResult<()> {
+        self.run_program(program).map_err(Into::into)
+    }
+
+    /// Starts streaming the given G-code commands and returns right after
+    /// sending as many of them as fit into the receive buffer of the GRBL. The
+    /// rest is sent by calling `poll` until it returns 0, which leaves room
+    /// for `feed_hold`, `resume` or `soft_reset` while the commands run.
+    fn start(&mut self, commands: Vec<String>) -> PyResult<()> {
+        self.queue(&commands);
+        self.send_queued(false).map_err(Into::into)
+    }
+
+    /// Processes the responses that the GRBL sent so far and sends as many of
+    /// the remaining commands of a started stream as fit into its receive
+    /// buffer, without waiting for the machine to move. Returns how many
+    /// commands were not yet responded to. Raises like `stream` if the GRBL
+    /// rejected a command, in which case the remaining commands are dropped.
+    fn poll(&mut self) -> PyResult<usize> {
+        self.poll_stream().map_err(Into::into)
+    }
+
+    /// Pauses the current motion by decelerating to a stop, without losing the
+    /// position. Messages that are in flight are kept and executed once the
+    /// motion is resumed.
+    fn feed_hold(&mut self) -> PyResult<()> {
+        self.write_realtime(FEED_HOLD).map_err(Into::into)
+    }
+
+    /// Resumes a motion that was paused by `feed_hold`.
+    fn resume(&mut self) -> PyResult<()> {
+        self.write_realtime(CYCLE_START).map_err(Into::into)
+    }
+
+    /// Cancels the current jog motion and discards the jog commands that are
+    /// still queued. Other motions are not affected.
+    fn cancel_jog(&mut self) -> PyResult<()> {
+        self.write_realtime(JOG_CANCEL).map_err(Into::into)
+    }
+
+    /// Aborts everything the GRBL is doing and resets it, discarding all
+    /// messages that are queued or in flight along with any error they
+    /// caused. The connection is initialized again afterwards, just like when
+    /// connecting, except that the lock is cleared with '$X' instead of homing
+    /// the machine unless `home` is `True`.
+    fn soft_reset(&mut self, home: Option<bool>) -> PyResult<()> {
+        self.reset(home.unwrap_or(false)).map_err(Into::into)
+    }
+
+    /// Returns the '$' settings of the GRBL as a `dict` that maps the number of
+    /// each setting to its value. Switches are given as `bool`, whole numbers
+    /// and masks as `int` and everything else as `float`.
+    fn settings(&mut self) -> PyResult<BTreeMap<u16, SettingValue>> {
+        self.query_settings().map_err(Into::into)
+    }
+
+    /// Changes the '$' setting with the given number, which is stored
+    /// permanently by the GRBL. Raises a `ValueError` if there is no such
+    /// setting or if it does not accept the value.
+    fn set_setting(&mut self, number: u16, value: f64) -> PyResult<()> {
+        let kind = SettingKind::of(number).ok_or_else(|| {
+            exc::ValueError::new(format!("unknown setting ${}", number))
+        })?;
+
+        let value = kind.create_value(value).ok_or_else(|| {
+            exc::ValueError::new(format!(
+                "setting ${} must be {}",
+                number,
+                kind.get_description()
+            ))
+        })?;
+
+        let message = format!("${}={}\n", number, value);
+        self.exchange(&message).map_err(Into::into)
+    }
+
+    /// Returns the coordinate offsets of the GRBL as a `dict` that maps their
+    /// names, like "G54" or "G92", to lists of coordinates. The tool length
+    /// offset "TLO" has a single coordinate, while the last probing position
+    /// "PRB" omits whether the probe succeeded.
+    fn offsets(&mut self) -> PyResult<BTreeMap<String, Vec<f64>>> {
+        self.query_offsets().map_err(Into::into)
+    }
+
+    /// Returns the active modes of the G-code parser as a list of words, e.g.
+    /// ["G0", "G54", "G17", "G21", "G90", "G94", "M5", "M9", "T0", "F0", "S0"].
+    fn parser_state(&mut self) -> PyResult<Vec<String>> {
+        self.query_parser_state().map_err(Into::into)
+    }
+
+    /// Queries the current state of the machine. Returns a `dict` with the
+    /// state, e.g. "Idle" or "Run", the machine and work positions, the feed
+    /// rate and spindle speed and the free space in the planner and receive
+    /// buffers. Fields that the GRBL does not report are set to `None`.
+    fn status(&mut self) -> PyResult<Status> {
+        self.query_status().map_err(Into::into)
+    }
+
+    /// Blocks until the machine has finished all of its movements. Raises a
+    /// `GRBLAlarm` if the machine is in an alarm state and a `GRBLError` if it
+    /// is held or its door is open, as it would never become idle on its own.
+    /// Raises an `IOError` if it is still moving after the timeout.
+    fn wait_until_idle(&mut self) -> PyResult<()> {
+        self.wait_idle().map_err(Into::into)
+    }
+}
+
+impl GRBL {
+    /// Creates a `GRBL` that communicates over the given `port`, which can be
+    /// a `Simulator` for testing without a machine. The init sequence is not
+    /// exchanged yet, and the baud rate and timeout of the `config` are
+    /// expected to be applied to the `port` already.
+    pub fn new(port: Box<dyn Transport>, config: Config) -> GRBL {
+        GRBL {
+            port,
+            config,
+            pen: Pen::default(),
+            buffer: [0; BUFFER_SIZE],
+            pos: 0,
+            in_flight: VecDeque::new(),
+            queued: VecDeque::new(),
+            error: None,
+        }
+    }
+
+    /// Tries to open the connection using the given `port`.
+    fn try_connect(port: &str, config: Config) -> io::Result<Self> {
+        let mut port = serialport::open(port)?;
+
+        port.set_baud_rate(config.baud_rate)?;
+        port.set_timeout(config.timeout)?;
+
+        Ok(Self::new(Box::new(port), config))
+    }
+
+    /// Sends initialization messages and sets some basic settings required when
+    /// connecting to a GRBL. The GRBL is unlocked by homing the machine if
+    /// `is_homing` is set.
+    fn exchange_init_sequence(&mut self, is_homing: bool) -> Result<(), Error> {
+        // Ignore everything up to the greeting, which includes the version and
+        // therefore differs between machines.
+        loop {
+            if let Response::Greeting(_) = Response::parse(&self.read()?) {
+                break;
+            }
+        }
+
+        // With homing enabled, the GRBL starts out in a "locked" state. We can
+        // unlock it either by homing or by clearing the lock, which is
+        // accepted even if it is not locked.
+        if is_homing {
+            self.exchange("$H\n")?;
+        } else {
+            self.exchange("$X\n")?;
+        }
+
+        let units = self.config.units.get_gcode();
+        self.exchange(&format!("{}\n", units))?;
+
+        // Set distance mode to absolute
+        self.exchange("G90\n")?;
+
+        let feed_rate = format!("F{}\n", self.config.feed_rate);
+        self.exchange(&feed_rate)?;
+
+        let startup = self.config.startup.clone();
+        self.send_commands(&startup)
+    }
+
+    /// Reads a single newline-delimited message from the given `port`. Returns
+    /// an `io::Error` if the underlying read operation failed, which may be
+    /// caused by a timeout on the port.
+    fn read(&mut self) -> io::Result<String> {
+        let mut start = 0;
+        loop {
+            if let Some(line) = self.get_line_from_buffer(start) {
+                return line.map_err(|error| {
+                    io::Error::new(io::ErrorKind::InvalidData, error)
+                });
+            }
+
+            start = self.pos;
+            self.pos += self.port.read(&mut self.buffer[self.pos..])?;
+        }
+    }
+
+    /// Inspects the internal buffer starting at `offset`. It stops at the first
+    /// newline character it finds, removing all characters leading up to it
+    /// from the buffer and returning them. Returns `None` if no newline
+    /// character was found.
+    fn get_line_from_buffer(
+        &mut self,
+        start: usize,
+    ) -> Option<Result<String, FromUtf8Error>> {
+        let found = self.buffer[start..self.pos]
+            .iter()
+            .position(|&byte| byte == b'\n');
+
+        found.map(|found| {
+            let line_length = start + found + 1;
+            let line = String::from_utf8(self.buffer[..line_length].to_vec());
+
+            // Move the characters following the line to the start of the
+            // buffer.
+            self.buffer[..self.pos].rotate_left(line_length);
+            self.pos -= line_length;
+
+            line
+        })
+    }
+
+    /// Writes a single newline-delimited message to the given `port`. Returns
+    /// an `io::Error` if the underlying write operation failed.
+    fn write(&mut self, message: &str) -> io::Result<()> {
+        assert!(message.ends_with('\n'));
+
+        self.port.write_all(&message.as_bytes())
+    }
+
+    /// Writes a single realtime command to the given `port`. These commands
+    /// consist of a single byte and are executed by the GRBL immediately,
+    /// without being queued like other messages.
+    fn write_realtime(&mut self, command: u8) -> io::Result<()> {
+        self.port.write_all(&[command])
+    }
+
+    /// Resets the GRBL using the realtime command and exchanges the init
+    /// sequence once it has restarted, which homes the machine only if
+    /// `is_homing` is set. Messages in flight are dropped, as the GRBL will
+    /// never respond to them, and so are queued messages.
+    fn reset(&mut self, is_homing: bool) -> Result<(), Error> {
+        self.write_realtime(SOFT_RESET)?;
+
+        self.in_flight.clear();
+        self.queued.clear();
+        self.error = None;
+
+        // Responses that were received before the reset are skipped together
+        // with the greeting.
+        self.exchange_init_sequence(is_homing)
+    }
+
+    /// Exchanges a message like a '$' command and returns the messages that
+    /// the GRBL sent in response before acknowledging it.
+    fn query(&mut self, message: &str) -> Result<Vec<String>, Error> {
+        // Messages that are sent in response to streamed commands could not
+        // be told apart otherwise.
+        self.flush()?;
+        self.send(message)?;
+
+        let mut lines = Vec::new();
+        while !self.in_flight.is_empty() {
+            if let Response::Message(line) = self.receive()? {
+                lines.push(line);
+            }
+        }
+
+        self.take_error().map(|_| lines)
+    }
+
+    fn query_settings(&mut self) -> Result<BTreeMap<u16, SettingValue>, Error> {
+        let lines = self.query("$$\n")?;
+        Ok(lines
+            .iter()
+            .filter_map(|line| SettingValue::parse(line))
+            .collect())
+    }
+
+    /// Reads the coordinate offsets from lines like "[G54:0.000,0.000,0.000]".
+    fn query_offsets(&mut self) -> Result<BTreeMap<String, Vec<f64>>, Error> {
+        let lines = self.query("$#\n")?;
+
+        let offsets = lines.iter().filter_map(|line| {
+            let line = line.trim_start_matches('[').trim_end_matches(']');
+            let mut parts = line.split(':');
+
+            let name = parts.next()?.to_string();
+            let coordinates = parts
+                .next()?
+                .split(',')
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .ok()?;
+
+            (name, coordinates).into()
+        });
+
+        Ok(offsets.collect())
+    }
+
+    /// Reads the parser state from a line like "[GC:G0 G54 G17 G21 ...]".
+    fn query_parser_state(&mut self) -> Result<Vec<String>, Error> {
+        let lines = self.query("$G\n")?;
+
+        let state = lines.iter().find(|line| line.starts_with("[GC:")).map_or(
+            Vec::new(),
+            |line| {
+                line["[GC:".len()..]
+                    .trim_end_matches(']')
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect()
+            },
+        );
+
+        Ok(state)
+    }
+
+    /// Sends the '?' realtime command and waits for the status report that is
+    /// sent in response. Responses to streamed messages received in the
+    /// meantime are processed as usual.
+    fn query_status(&mut self) -> io::Result<Status> {
+        self.write_realtime(STATUS_QUERY)?;
+
+        loop {
+            if let Response::Report(status) = self.receive()? {
+                return Ok(status);
+            }
+        }
+    }
+
+    /// Reads a single response and matches it to the oldest message in flight
+    /// if it is an 'ok' or an error. Errors and alarms are kept until they are
+    /// reported by `take_error`.
+    fn receive(&mut self) -> io::Result<Response> {
+        let response = Response::parse(&self.read()?);
+
+        match response {
+            Response::Ok => {
+                self.in_flight.pop_front();
+            }
+            Response::Error(code) => {
+                let message = self.in_flight.pop_front().unwrap_or_default();
+                let line = message.trim_end().to_string();
+
+                if self.error.is_none() {
+                    self.error = Error::Command { code, line }.into();
+                }
+            }
+            // An alarm resets the GRBL, which discards all messages that were
+            // not executed yet. The alarm takes precedence over other errors,
+            // because it stops the machine.
+            Response::Alarm(code) => {
+                self.in_flight.clear();
+                self.error = Error::Alarm(code).into();
+            }
+            Response::Report(_)
+            | Response::Greeting(_)
+            | Response::Message(_) => (),
+        }
+
+        Ok(response)
+    }
+
+    /// Returns the error that occurred while streaming, if any, and forgets
+    /// about it. Queued messages are dropped along with the error, so none of
+    /// them are sent after it.
+    fn take_error(&mut self) -> Result<(), Error> {
+        match self.error.take() {
+            Some(error) => {
+                self.queued.clear();
+                Err(error)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Returns whether the `message` can be sent right away, because it fits
+    /// into the receive buffer of the GRBL next to the messages in flight. A
+    /// message that would not even fit into the empty buffer is sent anyway
+    /// once nothing else is in flight, so the GRBL can reject it.
+    fn fits(&self, message: &str) -> bool {
+        let in_flight: usize = self.in_flight.iter().map(String::len).sum();
+
+        self.in_flight.is_empty() || in_flight + message.len() <= RX_BUFFER_SIZE
+    }
+
+    /// Sends a single newline-delimited message as soon as it fits into the
+    /// receive buffer of the GRBL, using the character counting protocol. The
+    /// message is not waited for, so responses are matched to messages in the
+    /// order in which they were sent.
+    fn send(&mut self, message: &str) -> Result<(), Error> {
+        self.take_error()?;
+
+        while !self.fits(message) {
+            self.receive()?;
+            self.take_error()?;
+        }
+
+        self.write(message)?;
+        self.in_flight.push_back(message.to_string());
+
+        Ok(())
+    }
+
+    /// Appends the given G-code commands, which do not need to end with a
+    /// newline, to the queued messages.
+    fn queue(&mut self, commands: &[String]) {
+        let messages = commands
+            .iter()
+            .map(|command| format!("{}\n", command.trim_end()));
+
+        self.queued.extend(messages);
+    }
+
+    /// Sends the queued messages in order. Unless `should_wait` is set, this
+    /// stops at the first message that does not fit into the receive buffer
+    /// instead of waiting for the GRBL to make room for it. The queue is
+    /// dropped if sending fails.
+    fn send_queued(&mut self, should_wait: bool) -> Result<(), Error> {
+        while let Some(message) = self.queued.front().cloned() {
+            if !should_wait && !self.fits(&message) {
+                break;
+            }
+
+            if let Err(error) = self.send(&message) {
+                self.queued.clear();
+                return Err(error);
+            }
+
+            self.queued.pop_front();
+        }
+
+        Ok(())
+    }
+
+    /// Streams the given G-code commands and waits until all of them were
+    /// responded to.
+    fn send_commands(&mut self, commands: &[String]) -> Result<(), Error> {
+        self.queue(commands);
+        self.flush()
+    }
+
+    /// Streams the commands of the `program` and waits until all of them were
+    /// responded to.
+    pub fn run_program(&mut self, program: &Program) -> Result<(), Error> {
+        self.send_commands(program.get_commands())
+    }
+
+    /// Queries the status until the machine is idle, for at most as long as
+    /// the timeout of the `config`.
+    fn wait_idle(&mut self) -> Result<(), Error> {
+        let start = Instant::now();
+
+        loop {
+            match self.query_status()?.state {
+                MachineState::Idle => return Ok(()),
+                MachineState::Alarm => return Err(Error::AlarmState),
+                state @ MachineState::Hold | state @ MachineState::Door => {
+                    return Err(Error::HaltedState(state))
+                }
+                _ if start.elapsed() >= self.config.timeout => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "machine did not become idle before the timeout",
+                    )
+                    .into())
+                }
+                _ => thread::sleep(Duration::from_millis(POLL_INTERVAL)),
+            }
+        }
+    }
+
+    /// Processes the responses received so far, which are read up to the
+    /// status report that the GRBL sends right away, even while the motion is
+    /// held. Then sends the queued messages that fit into the receive buffer.
+    /// Returns the number of messages that are queued or in flight.
+    fn poll_stream(&mut self) -> Result<usize, Error> {
+        self.query_status()?;
+        self.take_error()?;
+        self.send_queued(false)?;
+
+        Ok(self.queued.len() + self.in_flight.len())
+    }
+
+    /// Sends all queued messages and waits until every message in flight was
+    /// responded to.
+    fn flush(&mut self) -> Result<(), Error> {
+        self.send_queued(true)?;
+
+        while !self.in_flight.is_empty() {
+            self.receive()?;
+        }
+
+        self.take_error()
+    }
+
+    /// Writes a single newline-delimited message to the given `port` and waits
+    /// for the GRBL to respond to it, as well as to all messages that were
+    /// streamed or queued before. Returns an `Error` if either the read or
+    /// write failed, or if the GRBL responded with an error or an alarm.
+    fn exchange(&mut self, message: &str) -> Result<(), Error> {
+        self.queued.push_back(message.to_string());
+        self.flush()
+    }
+}
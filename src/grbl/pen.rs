@@ -0,0 +1,171 @@
+use crate::pyo3::prelude::*;
+
+use self::Pen::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_servo_and_waits_for_it_to_settle() {
+        let pen = Servo {
+            up: 30.0,
+            down: Some(5.0),
+            dwell: 0.25,
+        };
+
+        assert_eq!(pen.get_lower_commands(), vec!["M3 S5", "G4 P0.25"]);
+        assert_eq!(pen.get_raise_commands(), vec!["M3 S30", "G4 P0.25"]);
+    }
+
+    #[test]
+    fn lowers_default_servo_by_turning_off_spindle() {
+        let pen = Pen::default();
+
+        assert_eq!(pen.get_lower_commands(), vec!["M5"]);
+        assert_eq!(pen.get_raise_commands(), vec!["M3 S30"]);
+    }
+
+    #[test]
+    fn moves_z_axis_and_switches_laser() {
+        let pen = ZAxis {
+            up: 5.0,
+            down: -1.5,
+        };
+        assert_eq!(pen.get_lower_commands(), vec!["G0 Z-1.5"]);
+        assert_eq!(pen.get_raise_commands(), vec!["G0 Z5"]);
+
+        let laser = Laser { power: 800.0 };
+        assert_eq!(laser.get_lower_commands(), vec!["M4 S800"]);
+        assert_eq!(laser.get_raise_commands(), vec!["M5"]);
+    }
+}
+
+/// `Enum` that describes how the machine puts the pen onto the paper and lifts
+/// it off again.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pen {
+    /// A servo driven by the PWM signal of the spindle, which moves to the
+    /// position given by the spindle speed. Without a `down` position, it is
+    /// lowered by turning off the spindle. The machine dwells for the given
+    /// number of seconds after each move, until the servo has settled.
+    Servo {
+        up: f64,
+        down: Option<f64>,
+        dwell: f64,
+    },
+    /// A pen moved by the z-axis to the given heights.
+    ZAxis { up: f64, down: f64 },
+    /// A laser that burns the lines with the given power while it is switched
+    /// on by lowering it.
+    Laser { power: f64 },
+}
+
+impl Pen {
+    /// Returns the G-code commands that lower the pen.
+    pub fn get_lower_commands(&self) -> Vec<String> {
+        match *self {
+            Servo {
+                down: Some(down),
+                dwell,
+                ..
+            } => get_servo_commands(format!("M3 S{}", down), dwell),
+            Servo {
+                down: None, dwell, ..
+            } => get_servo_commands("M5".to_string(), dwell),
+            ZAxis { down, .. } => vec![format!("G0 Z{}", down)],
+            // The dynamic power mode turns the laser off while the machine is
+            // not moving, so it does not burn holes at the start of lines.
+            Laser { power } => vec![format!("M4 S{}", power)],
+        }
+    }
+
+    /// Returns the G-code commands that raise the pen.
+    pub fn get_raise_commands(&self) -> Vec<String> {
+        match *self {
+            Servo { up, dwell, .. } => {
+                get_servo_commands(format!("M3 S{}", up), dwell)
+            }
+            ZAxis { up, .. } => vec![format!("G0 Z{}", up)],
+            Laser { .. } => vec!["M5".to_string()],
+        }
+    }
+}
+
+impl Default for Pen {
+    /// Returns the servo of the original machine, which is lowered by turning
+    /// off the PWM signal.
+    fn default() -> Self {
+        Servo {
+            up: 30.0,
+            down: None,
+            dwell: 0.0,
+        }
+    }
+}
+
+impl<'a> FromPyObject<'a> for Pen {
+    /// Extracts a pen from a tuple of its kind followed by its parameters,
+    /// which is either `("servo", up, down, dwell)`, `("servo", up, dwell)`,
+    /// `("z-axis", up, down)` or `("laser", power)`.
+    fn extract(object: &'a PyObjectRef) -> PyResult<Self> {
+        let servo = <(String, f64, f64, f64)>::extract(object).ok();
+        let z_axis = <(String, f64, f64)>::extract(object).ok();
+        let laser = <(String, f64)>::extract(object).ok();
+
+        let pen = match (servo, z_axis, laser) {
+            (Some((ref kind, up, down, dwell)), _, _) if kind == "servo" => {
+                Servo {
+                    up,
+                    down: Some(down),
+                    dwell,
+                }
+            }
+            (_, Some((ref kind, up, dwell)), _) if kind == "servo" => Servo {
+                up,
+                down: None,
+                dwell,
+            },
+            (_, Some((ref kind, up, down)), _) if kind == "z-axis" => {
+                ZAxis { up, down }
+            }
+            (_, _, Some((ref kind, power))) if kind == "laser" => {
+                Laser { power }
+            }
+            _ => {
+                return Err(exc::ValueError::new(
+                    "expected a pen like ('servo', up, down, dwell), \
+                     ('servo', up, dwell), ('z-axis', up, down) or \
+                     ('laser', power)",
+                ))
+            }
+        };
+
+        match pen {
+            Servo { up, down, dwell }
+                if !(up >= 0.0
+                    && down.map_or(true, |down| down >= 0.0)
+                    && dwell >= 0.0) =>
+            {
+                Err(exc::ValueError::new(
+                    "servo positions and dwell must not be negative",
+                ))
+            }
+            Laser { power } if !(power > 0.0) => {
+                Err(exc::ValueError::new("laser power must be positive"))
+            }
+            _ => Ok(pen),
+        }
+    }
+}
+
+/// Returns the `command` that moves the servo, followed by a dwell if needed.
+fn get_servo_commands(command: String, dwell: f64) -> Vec<String> {
+    let mut commands = vec![command];
+
+    if dwell > 0.0 {
+        commands.push(format!("G4 P{}", dwell));
+    }
+
+    commands
+}
@@ -0,0 +1,28 @@
+use super::{Pen, Transform, Units};
+
+/// Describes where and how the solution of a puzzle is drawn by the machine.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlotSettings {
+    /// Maps the intersections of the grid onto the paper.
+    pub transform: Transform,
+    /// The feed rate of linear motions in units per minute.
+    pub feed_rate: f64,
+    pub units: Units,
+    pub pen: Pen,
+    /// The radius of the arcs that replace the corners of the routes, which
+    /// are drawn sharp if it is `None`.
+    pub corner_radius: Option<f64>,
+}
+
+impl Default for PlotSettings {
+    /// Returns the settings of the original machine.
+    fn default() -> Self {
+        PlotSettings {
+            transform: Transform::new([56.5, 130.5], 6.5, 0.0),
+            feed_rate: 2000.0,
+            units: Units::Millimeters,
+            pen: Pen::default(),
+            corner_radius: None,
+        }
+    }
+}
@@ -0,0 +1,416 @@
+use crate::{
+    data::{Cells, Edges},
+    planner,
+    pyo3::{class::PyObjectProtocol, prelude::*},
+    sheet,
+    smoothing::{self, Segment},
+    std::{fmt, fs},
+};
+
+use super::{Pen, PlotSettings, Transform, Units};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_route_between_header_and_footer() {
+        let edges: Edges = "+-+\n| |\n+-+".parse().unwrap();
+        let settings = PlotSettings {
+            transform: Transform::new([10.0, 20.0], 5.0, 0.0),
+            feed_rate: 1000.0,
+            units: Units::Millimeters,
+            pen: Pen::ZAxis { up: 2.0, down: 0.0 },
+            corner_radius: None,
+        };
+
+        let program = Program::from_edges(&edges, &settings);
+
+        assert_eq!(
+            program.to_string(),
+            "(Suriza solution)\n\
+             G21\n\
+             G90\n\
+             F1000\n\
+             G0 Z2\n\
+             G0 X10.000 Y15.000\n\
+             G0 Z0\n\
+             G1 X10.000 Y20.000\n\
+             G1 X15.000 Y20.000\n\
+             G1 X15.000 Y15.000\n\
+             G1 X10.000 Y15.000\n\
+             G0 Z2\n\
+             G0 X0.000 Y0.000\n\
+             M2\n"
+        );
+    }
+
+    #[test]
+    fn draws_sheet_with_dots_numbers_and_crosses() {
+        let cells: Cells = "+ +\n|2|\n+ +".parse().unwrap();
+        let edges: Edges = "+x+\n|  \n+ +".parse().unwrap();
+        let settings = PlotSettings {
+            pen: Pen::Laser { power: 100.0 },
+            ..PlotSettings::default()
+        };
+
+        let count_strokes = |program: Program| {
+            program
+                .get_commands()
+                .iter()
+                .filter(|command| command.starts_with("M4"))
+                .count()
+        };
+
+        let sheet = Program::from_puzzle(&cells, None, true, &settings);
+        assert_eq!(sheet.get_commands()[0], "(Suriza puzzle)");
+        assert_eq!(count_strokes(sheet), 5);
+
+        let crossed =
+            Program::from_puzzle(&cells, Some(&edges), true, &settings);
+        assert_eq!(count_strokes(crossed), 8);
+
+        let uncrossed =
+            Program::from_puzzle(&cells, Some(&edges), false, &settings);
+        assert_eq!(count_strokes(uncrossed), 6);
+    }
+
+    #[test]
+    fn merges_straight_runs_and_rounds_corners() {
+        let edges: Edges = "+-+-+\n|   |\n+-+-+".parse().unwrap();
+        let settings = PlotSettings {
+            transform: Transform::new([0.0, 10.0], 10.0, 0.0),
+            feed_rate: 1000.0,
+            units: Units::Millimeters,
+            pen: Pen::Laser { power: 100.0 },
+            corner_radius: Some(2.0),
+        };
+
+        let program = Program::from_edges(&edges, &settings);
+
+        assert_eq!(
+            program.get_commands()[5..15].join("\n"),
+            "G0 X2.000 Y0.000\n\
+             M4 S100\n\
+             G2 X0.000 Y2.000 I0.000 J2.000\n\
+             G1 X0.000 Y8.000\n\
+             G2 X2.000 Y10.000 I2.000 J0.000\n\
+             G1 X18.000 Y10.000\n\
+             G2 X20.000 Y8.000 I0.000 J-2.000\n\
+             G1 X20.000 Y2.000\n\
+             G2 X18.000 Y0.000 I-2.000 J0.000\n\
+             G1 X2.000 Y0.000"
+        );
+    }
+}
+
+/// A G-code program, which is built up command by command and can either be
+/// saved as a file or streamed to a `GRBL`.
+#[pyclass]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Program {
+    commands: Vec<String>,
+}
+
+impl Program {
+    /// Creates an empty program.
+    pub fn new() -> Program {
+        Program::default()
+    }
+
+    /// Creates a complete program that draws the routes of the `edges`, which
+    /// are mapped onto the paper by the transform of the `settings`.
+    pub fn from_edges(edges: &Edges, settings: &PlotSettings) -> Program {
+        let routes = map_routes(edges, settings);
+        Program::from_polylines("Suriza solution", routes, settings)
+    }
+
+    /// Creates a complete program that draws the puzzle given by the `cells`
+    /// as a sheet, i.e. the dots of the grid and the numbers of the cells,
+    /// together with the routes of the `edges` if they are given. Edges that
+    /// are marked with an x are crossed out if `with_crosses` is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `edges` do not have the same size as the `cells`.
+    pub fn from_puzzle(
+        cells: &Cells,
+        edges: Option<&Edges>,
+        with_crosses: bool,
+        settings: &PlotSettings,
+    ) -> Program {
+        let crossed = edges.filter(|_| with_crosses);
+        let mut polylines: Vec<_> = sheet::draw_sheet(cells, crossed)
+            .into_iter()
+            .map(|stroke| {
+                stroke
+                    .into_iter()
+                    .map(|point| settings.transform.apply(point))
+                    .collect()
+            })
+            .collect();
+
+        if let Some(edges) = edges {
+            polylines.extend(map_routes(edges, settings));
+        }
+
+        Program::from_polylines("Suriza puzzle", polylines, settings)
+    }
+
+    /// Creates a complete program that draws the `polylines`, which are given
+    /// in the coordinates of the machine. The header names the program by the
+    /// `title`, which must not contain parentheses, and sets up the machine,
+    /// after which the polylines are drawn in the order that keeps the travel
+    /// between them short, starting from the origin of the machine. Straight
+    /// runs are drawn as single moves, and corners are rounded if the
+    /// `settings` have a corner radius. Corners are rounded before the order
+    /// is planned, so the travel leads to where the rounded paths start. The
+    /// program ends by moving back to the origin.
+    pub fn from_polylines(
+        title: &str,
+        polylines: Vec<Vec<[f64; 2]>>,
+        settings: &PlotSettings,
+    ) -> Program {
+        let mut program = Program::new();
+
+        program.push(format!("({})", title));
+        program.push(settings.units.get_gcode());
+        program.push("G90");
+        program.push(format!("F{}", settings.feed_rate));
+        program.raise_pen(&settings.pen);
+
+        let polylines: Vec<_> = polylines
+            .iter()
+            .filter(|polyline| !polyline.is_empty())
+            .map(|polyline| smoothing::merge_collinear(polyline))
+            .collect();
+
+        match settings.corner_radius {
+            Some(radius) => {
+                let paths: Vec<_> = polylines
+                    .iter()
+                    .filter_map(|polyline| {
+                        smoothing::round_corners(polyline, radius)
+                    })
+                    .collect();
+
+                for path in planner::plan(paths, [0.0, 0.0]) {
+                    program.draw_path(
+                        path.start,
+                        &path.segments,
+                        &settings.pen,
+                    );
+                }
+            }
+            None => {
+                for polyline in planner::plan(polylines, [0.0, 0.0]) {
+                    program.draw_polyline(&polyline, &settings.pen);
+                }
+            }
+        }
+
+        program.seek_to(0.0, 0.0);
+        program.push("M2");
+
+        program
+    }
+
+    /// Returns the commands of the program, without newlines.
+    pub fn get_commands(&self) -> &[String] {
+        &self.commands
+    }
+
+    /// Appends a single command.
+    pub fn push<S: Into<String>>(&mut self, command: S) {
+        self.commands.push(command.into());
+    }
+
+    /// Appends a rapid motion to the given position.
+    pub fn seek_to(&mut self, x: f64, y: f64) {
+        self.push(format!("G0 X{:.3} Y{:.3}", x, y));
+    }
+
+    /// Appends a linear motion to the given position at the feed rate.
+    pub fn move_to(&mut self, x: f64, y: f64) {
+        self.push(format!("G1 X{:.3} Y{:.3}", x, y));
+    }
+
+    /// Appends a circular motion at the feed rate from the current `position`
+    /// around the `center` to the `end`.
+    pub fn arc_to(
+        &mut self,
+        position: [f64; 2],
+        end: [f64; 2],
+        center: [f64; 2],
+        is_clockwise: bool,
+    ) {
+        self.push(format!(
+            "{} X{:.3} Y{:.3} I{:.3} J{:.3}",
+            if is_clockwise { "G2" } else { "G3" },
+            end[0],
+            end[1],
+            center[0] - position[0],
+            center[1] - position[1]
+        ));
+    }
+
+    /// Appends the commands that draw the `polyline`, which is a single dot if
+    /// it consists of one point. The pen is expected to be raised before and
+    /// is raised again afterwards.
+    pub fn draw_polyline(&mut self, polyline: &[[f64; 2]], pen: &Pen) {
+        if let Some((start, rest)) = polyline.split_first() {
+            self.seek_to(start[0], start[1]);
+            self.lower_pen(pen);
+
+            for position in rest {
+                self.move_to(position[0], position[1]);
+            }
+
+            self.raise_pen(pen);
+        }
+    }
+
+    /// Appends the commands that draw the path from the `start` along the
+    /// `segments`. Like for `draw_polyline`, the pen is expected to be raised
+    /// before and is raised again afterwards.
+    pub fn draw_path(
+        &mut self,
+        start: [f64; 2],
+        segments: &[Segment],
+        pen: &Pen,
+    ) {
+        self.seek_to(start[0], start[1]);
+        self.lower_pen(pen);
+
+        let mut position = start;
+        for segment in segments {
+            match *segment {
+                Segment::Line(end) => self.move_to(end[0], end[1]),
+                Segment::Arc {
+                    end,
+                    center,
+                    is_clockwise,
+                } => self.arc_to(position, end, center, is_clockwise),
+            }
+
+            position = segment.get_end();
+        }
+
+        self.raise_pen(pen);
+    }
+
+    /// Appends the commands that lower the `pen`.
+    pub fn lower_pen(&mut self, pen: &Pen) {
+        self.commands.extend(pen.get_lower_commands());
+    }
+
+    /// Appends the commands that raise the `pen`.
+    pub fn raise_pen(&mut self, pen: &Pen) {
+        self.commands.extend(pen.get_raise_commands());
+    }
+}
+
+/// Returns the routes of the `edges` in the coordinates of the machine.
+fn map_routes(edges: &Edges, settings: &PlotSettings) -> Vec<Vec<[f64; 2]>> {
+    let to_position = |(column, row): (usize, usize)| {
+        settings.transform.apply([column as f64, row as f64])
+    };
+
+    edges
+        .get_routes()
+        .into_iter()
+        .map(|route| route.into_iter().map(to_position).collect())
+        .collect()
+}
+
+#[pymethods]
+impl Program {
+    /// Creates the program that draws the given solved `edges` onto a puzzle,
+    /// whose intersections are mapped onto the paper by the `transform`. The
+    /// `feed_rate`, `units` and `pen` are optional and default to those of the
+    /// original machine. The pen is given in the same format as for
+    /// `GRBL.set_pen`. Corners are rounded with arcs of the `corner_radius` if
+    /// it is given.
+    ///
+    /// If a `puzzle` is given, the program draws the whole sheet, i.e. the dots
+    /// and numbers of the puzzle, with the `edges` being optional in that case.
+    /// Edges marked with an x are crossed out if `crosses` is `True`.
+    #[new]
+    fn __new__(
+        obj: &PyRawObject,
+        edges: Option<Edges>,
+        transform: &Transform,
+        feed_rate: Option<f64>,
+        units: Option<Units>,
+        pen: Option<Pen>,
+        corner_radius: Option<f64>,
+        puzzle: Option<Cells>,
+        crosses: Option<bool>,
+    ) -> PyResult<()> {
+        if let Some(ref puzzle) = puzzle {
+            puzzle.check_same_size(edges.as_ref())?;
+        }
+
+        if feed_rate.map_or(false, |feed_rate| !(feed_rate > 0.0)) {
+            return Err(exc::ValueError::new("feed rate must be positive"));
+        }
+
+        if corner_radius.map_or(false, |radius| !(radius > 0.0)) {
+            return Err(exc::ValueError::new("corner radius must be positive"));
+        }
+
+        let default = PlotSettings::default();
+        let settings = PlotSettings {
+            transform: transform.clone(),
+            feed_rate: feed_rate.unwrap_or(default.feed_rate),
+            units: units.unwrap_or(default.units),
+            pen: pen.unwrap_or(default.pen),
+            corner_radius,
+        };
+
+        let program = match (puzzle, edges) {
+            (Some(puzzle), edges) => Program::from_puzzle(
+                &puzzle,
+                edges.as_ref(),
+                crosses.unwrap_or(false),
+                &settings,
+            ),
+            (None, Some(edges)) => Program::from_edges(&edges, &settings),
+            (None, None) => {
+                return Err(exc::ValueError::new("edges or puzzle required"))
+            }
+        };
+
+        obj.init(move |_| program)
+    }
+
+    /// Returns the commands of the program as a list of strings.
+    fn commands(&self) -> PyResult<Vec<String>> {
+        Ok(self.commands.clone())
+    }
+
+    /// Writes the program to the file at `path`, usually with the extension
+    /// ".nc", so it can be previewed in a G-code viewer or run again later.
+    fn save(&self, path: String) -> PyResult<()> {
+        fs::write(path, self.to_string()).map_err(Into::into)
+    }
+}
+
+#[pyproto]
+impl<'p> PyObjectProtocol<'p> for Program {
+    fn __str__(&self) -> PyResult<String> {
+        Ok(self.to_string())
+    }
+}
+
+impl fmt::Display for Program {
+    /// Formats the program as the content of a G-code file, with one command
+    /// on each line.
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for command in &self.commands {
+            writeln!(formatter, "{}", command)?;
+        }
+
+        Ok(())
+    }
+}
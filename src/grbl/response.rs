@@ -0,0 +1,79 @@
+use super::Status;
+
+use self::Response::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_responses() {
+        assert_eq!(Response::parse("ok\r\n"), Ok);
+        assert_eq!(Response::parse("error:22\r\n"), Error(22));
+        assert_eq!(Response::parse("ALARM:1\r\n"), Alarm(1));
+        assert_eq!(
+            Response::parse("Grbl 0.9j ['$' for help]\r\n"),
+            Greeting("Grbl 0.9j ['$' for help]".to_string())
+        );
+        assert_eq!(
+            Response::parse("[MSG:'$H'|'$X' to unlock]\r\n"),
+            Message("[MSG:'$H'|'$X' to unlock]".to_string())
+        );
+    }
+
+    #[test]
+    fn classifies_status_reports() {
+        match Response::parse("<Run|MPos:0.000,0.000,0.000|FS:500,0>\r\n") {
+            Report(status) => assert_eq!(status.feed_rate, Some(500.0)),
+            response => panic!("unexpected response {:?}", response),
+        }
+    }
+}
+
+/// `Enum` that represents a single line sent by the GRBL.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Response {
+    /// The command was executed, or queued in case of motions.
+    Ok,
+    /// The command was rejected with the given error code.
+    Error(u8),
+    /// The machine entered an alarm state for the reason given by the code.
+    Alarm(u8),
+    /// A status report sent in response to the '?' realtime command.
+    Report(Status),
+    /// The greeting sent once the GRBL has started or was reset, which names its
+    /// version, e.g. "Grbl 1.1f ['$' for help]".
+    Greeting(String),
+    /// Any other message, like feedback messages or the output of '$'
+    /// commands.
+    Message(String),
+}
+
+impl Response {
+    /// Classifies the given line, which may still contain the line ending.
+    pub fn parse(line: &str) -> Response {
+        let line = line.trim_end();
+
+        let code = |prefix: &str| {
+            if line.starts_with(prefix) {
+                line[prefix.len()..].parse().ok()
+            } else {
+                None
+            }
+        };
+
+        if line == "ok" {
+            Ok
+        } else if let Some(code) = code("error:") {
+            Error(code)
+        } else if let Some(code) = code("ALARM:") {
+            Alarm(code)
+        } else if let Some(status) = Status::parse(line) {
+            Report(status)
+        } else if line.starts_with("Grbl") {
+            Greeting(line.to_string())
+        } else {
+            Message(line.to_string())
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use super::SettingValue;
+
+use self::SettingKind::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_values_of_settings() {
+        let pulse = SettingKind::of(0).unwrap();
+        assert_eq!(pulse.create_value(10.0), Some(SettingValue::Integer(10)));
+        assert_eq!(pulse.create_value(2.0), None);
+        assert_eq!(pulse.create_value(10.5), None);
+
+        let laser_mode = SettingKind::of(32).unwrap();
+        assert_eq!(
+            laser_mode.create_value(1.0),
+            Some(SettingValue::Boolean(true))
+        );
+        assert_eq!(laser_mode.create_value(2.0), None);
+
+        let steps = SettingKind::of(100).unwrap();
+        assert_eq!(steps.create_value(80.0), Some(SettingValue::Decimal(80.0)));
+        assert_eq!(steps.create_value(-1.0), None);
+
+        assert_eq!(SettingKind::of(7), None);
+    }
+}
+
+/// `Enum` that describes which values a '$' setting of GRBL 1.1 accepts.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SettingKind {
+    /// A switch that is either 0 or 1.
+    Boolean,
+    /// A whole number in the given range, which includes bit masks.
+    Integer { min: u32, max: u32 },
+    /// A number that must not be negative, like steps per mm or rates.
+    Decimal,
+}
+
+impl SettingKind {
+    /// Returns the kind of the setting with the given number, or `None` if
+    /// there is no such setting.
+    pub fn of(number: u16) -> Option<SettingKind> {
+        let integer = |min, max| Integer { min, max };
+
+        let kind = match number {
+            // Step pulse and step idle delay
+            0 => integer(3, 255),
+            1 => integer(0, 255),
+            // Masks of the axes whose step or direction signals are inverted
+            2 | 3 => integer(0, 7),
+            4 | 5 | 6 => Boolean,
+            // Status report mask
+            10 => integer(0, 3),
+            11 | 12 => Decimal,
+            13 => Boolean,
+            20 | 21 | 22 => Boolean,
+            // Mask of the axes that home in the negative direction
+            23 => integer(0, 7),
+            24 | 25 => Decimal,
+            // Homing debounce
+            26 => integer(0, 65_535),
+            27 => Decimal,
+            30 | 31 => Decimal,
+            32 => Boolean,
+            // Steps per mm, maximum rates, accelerations and maximum travels
+            // of the three axes
+            100..=102 | 110..=112 | 120..=122 | 130..=132 => Decimal,
+            _ => return None,
+        };
+
+        kind.into()
+    }
+
+    /// Returns a description of the values that are accepted, which completes
+    /// a sentence like "The setting must be ...".
+    pub fn get_description(self) -> String {
+        match self {
+            Boolean => "0 or 1".to_string(),
+            Integer { min, max } => {
+                format!("a whole number from {} to {}", min, max)
+            }
+            Decimal => "a number that is not negative".to_string(),
+        }
+    }
+
+    /// Returns the value as the type of this kind, or `None` if it is not
+    /// accepted.
+    pub fn create_value(self, value: f64) -> Option<SettingValue> {
+        match self {
+            Boolean if value == 0.0 || value == 1.0 => {
+                SettingValue::Boolean(value == 1.0).into()
+            }
+            Integer { min, max }
+                if value.fract() == 0.0
+                    && value >= f64::from(min)
+                    && value <= f64::from(max) =>
+            {
+                SettingValue::Integer(value as u32).into()
+            }
+            Decimal if value.is_finite() && value >= 0.0 => {
+                SettingValue::Decimal(value).into()
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{pyo3::prelude::*, std::fmt};
+
+use super::SettingKind;
+
+use self::SettingValue::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_settings_by_their_kind() {
+        assert_eq!(SettingValue::parse("$0=10\r\n"), Some((0, Integer(10))));
+        assert_eq!(SettingValue::parse("$22=1"), Some((22, Boolean(true))));
+        assert_eq!(
+            SettingValue::parse("$100=250.000 (x, step/mm)"),
+            Some((100, Decimal(250.0)))
+        );
+        assert_eq!(SettingValue::parse("$N0="), None);
+    }
+}
+
+/// The value of a '$' setting, typed according to its `SettingKind`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SettingValue {
+    Boolean(bool),
+    Integer(u32),
+    Decimal(f64),
+}
+
+impl SettingValue {
+    /// Parses a line like "$100=250.000" as it is sent in response to the '$$'
+    /// command, returning the number of the setting and its value. Settings
+    /// that are unknown or have unexpected values are read as decimals, since
+    /// variants of GRBL may add their own settings.
+    pub fn parse(line: &str) -> Option<(u16, SettingValue)> {
+        if !line.starts_with('$') {
+            return None;
+        }
+
+        let mut parts = line[1..].splitn(2, '=');
+        let number = parts.next()?.parse().ok()?;
+        // Older versions follow the value with a description.
+        let value = parts.next()?.split_whitespace().next()?.parse().ok()?;
+
+        let value = SettingKind::of(number)
+            .and_then(|kind| kind.create_value(value))
+            .unwrap_or(Decimal(value));
+
+        (number, value).into()
+    }
+}
+
+impl fmt::Display for SettingValue {
+    /// Formats the value like it is written in a '$' command.
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Boolean(value) => write!(formatter, "{}", value as u8),
+            Integer(value) => write!(formatter, "{}", value),
+            Decimal(value) => write!(formatter, "{}", value),
+        }
+    }
+}
+
+impl ToPyObject for SettingValue {
+    /// Converts the value into a `bool`, `int` or `float`.
+    fn to_object(&self, py: Python) -> PyObject {
+        match *self {
+            Boolean(value) => value.to_object(py),
+            Integer(value) => value.to_object(py),
+            Decimal(value) => value.to_object(py),
+        }
+    }
+}
@@ -0,0 +1,557 @@
+use crate::std::{
+    collections::{BTreeMap, VecDeque},
+    io, mem,
+};
+
+use super::{
+    SettingKind, CYCLE_START, FEED_HOLD, RX_BUFFER_SIZE, SOFT_RESET,
+    STATUS_QUERY,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::std::io::prelude::*;
+
+    fn exchange(simulator: &mut Simulator, message: &str) -> String {
+        simulator.write_all(message.as_bytes()).unwrap();
+
+        let mut response = Vec::new();
+        let mut buffer = [0; 16];
+        while let Ok(length) = simulator.read(&mut buffer) {
+            response.extend_from_slice(&buffer[..length]);
+        }
+
+        String::from_utf8(response).unwrap()
+    }
+
+    fn unlocked() -> Simulator {
+        let mut simulator = Simulator::new();
+        exchange(&mut simulator, "$X\n");
+        simulator
+    }
+
+    #[test]
+    fn greets_and_waits_for_unlock() {
+        let mut simulator = Simulator::new();
+
+        let greeting = exchange(&mut simulator, "");
+        assert!(greeting.contains("Grbl 1.1f"));
+        assert!(greeting.contains("'$H'|'$X' to unlock"));
+
+        assert_eq!(exchange(&mut simulator, "G0 X1\n"), "error:9\r\n");
+        assert_eq!(
+            exchange(&mut simulator, "$X\n"),
+            "[MSG:Caution: Unlocked]\r\nok\r\n"
+        );
+        assert_eq!(exchange(&mut simulator, "G0 X1\n"), "ok\r\n");
+    }
+
+    #[test]
+    fn tracks_position_and_spindle() {
+        let mut simulator = unlocked();
+
+        exchange(&mut simulator, "G21 G90 F2000\nG1 X10 Y5\nM3 S30\n");
+        exchange(&mut simulator, "G91\n(relative) G1 X-2.5 ; left\n");
+
+        assert_eq!(simulator.get_position(), [7.5, 5.0, 0.0]);
+        assert_eq!(simulator.get_spindle_speed(), Some(30.0));
+        assert_eq!(
+            exchange(&mut simulator, "?"),
+            "<Idle|MPos:7.500,5.000,0.000|FS:2000,30|Bf:15,128>\r\n"
+        );
+
+        exchange(&mut simulator, "M5\n");
+        assert_eq!(simulator.get_spindle_speed(), None);
+    }
+
+    #[test]
+    fn rejects_invalid_commands() {
+        let mut simulator = unlocked();
+
+        assert_eq!(exchange(&mut simulator, "G1 X1\n"), "error:22\r\n");
+        assert_eq!(exchange(&mut simulator, "G5\n"), "error:20\r\n");
+        assert_eq!(exchange(&mut simulator, "X1.2.3\n"), "error:2\r\n");
+        assert_eq!(exchange(&mut simulator, "#1\n"), "error:1\r\n");
+        assert_eq!(exchange(&mut simulator, "$Q\n"), "error:3\r\n");
+        assert_eq!(exchange(&mut simulator, "$7=1\n"), "error:3\r\n");
+        assert_eq!(exchange(&mut simulator, "$100=-1\n"), "error:4\r\n");
+    }
+
+    #[test]
+    fn responds_when_read() {
+        let mut simulator = unlocked();
+
+        simulator.write_all(b"G0 X1\nG0 Y2\n").unwrap();
+        assert_eq!(simulator.get_position(), [0.0, 0.0, 0.0]);
+        assert_eq!(
+            exchange(&mut simulator, "?"),
+            "<Idle|MPos:0.000,0.000,0.000|FS:0,0|Bf:15,116>\r\nok\r\nok\r\n"
+        );
+        assert_eq!(simulator.get_position(), [1.0, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn fails_when_receive_buffer_overflows() {
+        let mut simulator = unlocked();
+        let messages = "G0 X1\n".repeat(RX_BUFFER_SIZE / 6);
+
+        simulator.write_all(messages.as_bytes()).unwrap();
+        assert!(simulator.write_all(b"G0 X2\n").is_err());
+    }
+
+    #[test]
+    fn keeps_messages_while_held() {
+        let mut simulator = unlocked();
+
+        assert_eq!(exchange(&mut simulator, "!G0 X1\n"), "");
+        assert_eq!(
+            exchange(&mut simulator, "?"),
+            "<Hold:0|MPos:0.000,0.000,0.000|FS:0,0|Bf:15,122>\r\n"
+        );
+
+        assert_eq!(exchange(&mut simulator, "~"), "ok\r\n");
+        assert_eq!(simulator.get_position(), [1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn stores_settings() {
+        let mut simulator = unlocked();
+
+        assert_eq!(exchange(&mut simulator, "$100=80\n"), "ok\r\n");
+        assert!(exchange(&mut simulator, "$$\n").contains("$100=80\r\n"));
+
+        exchange(&mut simulator, "$22=0\n");
+        assert_eq!(exchange(&mut simulator, "$H\n"), "error:5\r\n");
+    }
+}
+
+/// The default settings of GRBL 1.1, except that homing is enabled.
+const SETTINGS: [(u16, f64); 34] = [
+    (0, 10.0),
+    (1, 25.0),
+    (2, 0.0),
+    (3, 0.0),
+    (4, 0.0),
+    (5, 0.0),
+    (6, 0.0),
+    (10, 1.0),
+    (11, 0.01),
+    (12, 0.002),
+    (13, 0.0),
+    (20, 0.0),
+    (21, 0.0),
+    (22, 1.0),
+    (23, 0.0),
+    (24, 25.0),
+    (25, 500.0),
+    (26, 250.0),
+    (27, 1.0),
+    (30, 1000.0),
+    (31, 0.0),
+    (32, 0.0),
+    (100, 250.0),
+    (101, 250.0),
+    (102, 250.0),
+    (110, 500.0),
+    (111, 500.0),
+    (112, 500.0),
+    (120, 10.0),
+    (121, 10.0),
+    (122, 10.0),
+    (130, 200.0),
+    (131, 200.0),
+    (132, 200.0),
+];
+
+const GREETING: &str = "\r\nGrbl 1.1f ['$' for help]\r\n\
+                        [MSG:'$H'|'$X' to unlock]\r\n";
+
+/// A GRBL that runs in the same process, which allows using the `GRBL` without
+/// a machine attached. It understands the commands sent by the `GRBL` and keeps
+/// track of the position and the spindle, which controls the pen. Messages
+/// wait in the receive buffer until the responses are read, when they are
+/// executed instantly, so the machine is idle whenever it is asked. During a
+/// feed hold, they stay in the receive buffer until the cycle is resumed.
+/// Writing more than fits into the receive buffer fails instead of losing
+/// characters.
+///
+/// Like a real GRBL with homing enabled, the simulator starts out locked until
+/// it is either homed or unlocked, which it also requires after a reset.
+pub struct Simulator {
+    /// Characters of the message that is currently received.
+    input: Vec<u8>,
+    /// Messages that were received but not executed yet, without their
+    /// newline characters.
+    received: VecDeque<String>,
+    /// Responses that were not read yet.
+    output: VecDeque<u8>,
+    is_locked: bool,
+    /// Whether a feed hold is active, which keeps the received messages from
+    /// being executed until the cycle is resumed.
+    is_held: bool,
+    position: [f64; 3],
+    is_absolute: bool,
+    is_metric: bool,
+    /// Whether the current motion mode moves at the feed rate, like G1, G2
+    /// and G3, instead of rapidly like G0.
+    is_linear: bool,
+    feed_rate: Option<f64>,
+    spindle_speed: f64,
+    is_spindle_on: bool,
+    settings: BTreeMap<u16, f64>,
+}
+
+impl Simulator {
+    /// Creates a simulator that has just been started and sent its greeting.
+    pub fn new() -> Simulator {
+        Simulator {
+            input: Vec::new(),
+            received: VecDeque::new(),
+            output: GREETING.bytes().collect(),
+            is_locked: true,
+            is_held: false,
+            position: [0.0; 3],
+            is_absolute: true,
+            is_metric: true,
+            is_linear: false,
+            feed_rate: None,
+            spindle_speed: 0.0,
+            is_spindle_on: false,
+            settings: SETTINGS.iter().cloned().collect(),
+        }
+    }
+
+    /// Returns the position of the machine in mm.
+    pub fn get_position(&self) -> [f64; 3] {
+        self.position
+    }
+
+    /// Returns the speed of the spindle, or `None` if it is turned off.
+    pub fn get_spindle_speed(&self) -> Option<f64> {
+        if self.is_spindle_on {
+            self.spindle_speed.into()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of bytes in the receive buffer, including the
+    /// newline characters of the received messages.
+    fn get_buffered_length(&self) -> usize {
+        let received: usize =
+            self.received.iter().map(|message| message.len() + 1).sum();
+
+        received + self.input.len()
+    }
+
+    /// Executes the received messages in order, unless a feed hold is active.
+    fn execute_received(&mut self) {
+        while !self.is_held {
+            match self.received.pop_front() {
+                Some(message) => self.execute(&message),
+                None => break,
+            }
+        }
+    }
+
+    fn respond(&mut self, message: &str) {
+        self.output.extend(message.bytes());
+        self.output.extend(b"\r\n");
+    }
+
+    fn report_status(&mut self) {
+        let state = if self.is_locked {
+            "Alarm"
+        } else if self.is_held {
+            "Hold:0"
+        } else {
+            "Idle"
+        };
+        let position = self.position;
+        let feed_rate = self.feed_rate.unwrap_or(0.0);
+        let spindle_speed = self.get_spindle_speed().unwrap_or(0.0);
+
+        let available_bytes = RX_BUFFER_SIZE - self.get_buffered_length();
+
+        // Motions are executed instantly, so the planner buffer is empty.
+        let status = format!(
+            "<{}|MPos:{:.3},{:.3},{:.3}|FS:{},{}|Bf:15,{}>",
+            state,
+            position[0],
+            position[1],
+            position[2],
+            feed_rate,
+            spindle_speed,
+            available_bytes
+        );
+        self.respond(&status);
+    }
+
+    /// Restarts the simulator, which keeps the position like a real GRBL that
+    /// was not in motion, as well as the settings. Responses that were not
+    /// read yet are kept too, followed by the greeting.
+    fn reset(&mut self) {
+        let position = self.position;
+        let settings = mem::replace(&mut self.settings, BTreeMap::new());
+        let mut output = mem::replace(&mut self.output, VecDeque::new());
+
+        *self = Simulator::new();
+        self.position = position;
+        self.settings = settings;
+
+        output.append(&mut self.output);
+        self.output = output;
+    }
+
+    /// Executes a single message and responds to it with either 'ok' or an
+    /// error code.
+    fn execute(&mut self, message: &str) {
+        let mut command = String::new();
+        let mut is_comment = false;
+
+        // Comments are either enclosed in parentheses or follow a semicolon.
+        for character in message.chars() {
+            match character {
+                ';' if !is_comment => break,
+                '(' => is_comment = true,
+                ')' => is_comment = false,
+                _ if is_comment || character.is_whitespace() => (),
+                _ => command.push(character.to_ascii_uppercase()),
+            }
+        }
+
+        let result = if command.starts_with('$') {
+            self.execute_system_command(&command)
+        } else if command.is_empty() {
+            Ok(())
+        } else if self.is_locked {
+            Err(9)
+        } else {
+            self.execute_gcode(&command)
+        };
+
+        match result {
+            Ok(()) => self.respond("ok"),
+            Err(code) => self.respond(&format!("error:{}", code)),
+        }
+    }
+
+    fn execute_system_command(&mut self, command: &str) -> Result<(), u8> {
+        match command {
+            "$" => self.respond(
+                "[HLP:$$ $# $G $I $N $x=val $Nx=line $J=line \
+                 $SLP $C $X $H ~ ! ? ctrl-x]",
+            ),
+            "$$" => {
+                let settings: Vec<_> = self
+                    .settings
+                    .iter()
+                    .map(|(number, value)| format!("${}={}", number, value))
+                    .collect();
+
+                for setting in settings {
+                    self.respond(&setting);
+                }
+            }
+            "$#" => {
+                for name in &["G54", "G55", "G56", "G57", "G58", "G59"] {
+                    self.respond(&format!("[{}:0.000,0.000,0.000]", name));
+                }
+                self.respond("[G28:0.000,0.000,0.000]");
+                self.respond("[G30:0.000,0.000,0.000]");
+                self.respond("[G92:0.000,0.000,0.000]");
+                self.respond("[TLO:0.000]");
+                self.respond("[PRB:0.000,0.000,0.000:0]");
+            }
+            "$G" => self.report_parser_state(),
+            "$H" => {
+                if self.settings[&22] == 0.0 {
+                    return Err(5);
+                }
+
+                self.position = [0.0; 3];
+                self.is_locked = false;
+            }
+            "$X" => {
+                self.respond("[MSG:Caution: Unlocked]");
+                self.is_locked = false;
+            }
+            _ => return self.change_setting(&command[1..]),
+        }
+
+        Ok(())
+    }
+
+    /// Changes a setting given as "N=value".
+    fn change_setting(&mut self, assignment: &str) -> Result<(), u8> {
+        let mut parts = assignment.splitn(2, '=');
+        let number = parts.next().and_then(|number| number.parse().ok());
+        let number = number.ok_or(3)?;
+        let value = parts.next().ok_or(3)?.parse().map_err(|_| 2)?;
+
+        let kind = SettingKind::of(number).ok_or(3)?;
+        if value < 0.0 {
+            return Err(4);
+        }
+
+        kind.create_value(value).ok_or(3)?;
+        self.settings.insert(number, value);
+
+        Ok(())
+    }
+
+    fn report_parser_state(&mut self) {
+        let state = format!(
+            "[GC:G{} G54 G17 G{} G{} G94 M{} M9 T0 F{} S{}]",
+            if self.is_linear { 1 } else { 0 },
+            if self.is_metric { 21 } else { 20 },
+            if self.is_absolute { 90 } else { 91 },
+            if self.is_spindle_on { 3 } else { 5 },
+            self.feed_rate.unwrap_or(0.0),
+            self.spindle_speed
+        );
+        self.respond(&state);
+    }
+
+    fn execute_gcode(&mut self, command: &str) -> Result<(), u8> {
+        let words = parse_words(command)?;
+        let mut target = [None; 3];
+
+        for &(letter, value) in &words {
+            match (letter, value as u32) {
+                ('G', 0) => self.is_linear = false,
+                // Arcs end at the same position as a linear motion would.
+                ('G', 1) | ('G', 2) | ('G', 3) => self.is_linear = true,
+                // Dwelling takes no time in the simulation.
+                ('G', 4) => (),
+                ('G', 20) => self.is_metric = false,
+                ('G', 21) => self.is_metric = true,
+                ('G', 90) => self.is_absolute = true,
+                ('G', 91) => self.is_absolute = false,
+                ('M', 2) | ('M', 30) => self.is_spindle_on = false,
+                ('M', 3) | ('M', 4) => self.is_spindle_on = true,
+                ('M', 5) => self.is_spindle_on = false,
+                ('F', _) => self.feed_rate = value.into(),
+                ('S', _) => self.spindle_speed = value,
+                // The dwell time and the offsets to the center of an arc.
+                ('P', _) | ('I', _) | ('J', _) => (),
+                ('X', _) => target[0] = value.into(),
+                ('Y', _) => target[1] = value.into(),
+                ('Z', _) => target[2] = value.into(),
+                _ => return Err(20),
+            }
+        }
+
+        let has_target = target.iter().any(Option::is_some);
+        if has_target && self.is_linear && self.feed_rate.is_none() {
+            return Err(22);
+        }
+
+        let scale = if self.is_metric { 1.0 } else { 25.4 };
+        for (position, target) in self.position.iter_mut().zip(&target) {
+            if let Some(target) = *target {
+                if self.is_absolute {
+                    *position = target * scale;
+                } else {
+                    *position += target * scale;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits a command without whitespace into its words, each of which is a
+/// letter followed by a number, e.g. "G1X10.5" into [('G', 1), ('X', 10.5)].
+fn parse_words(command: &str) -> Result<Vec<(char, f64)>, u8> {
+    let mut words = Vec::new();
+    let mut characters = command.chars().peekable();
+
+    while let Some(letter) = characters.next() {
+        if !letter.is_ascii_uppercase() {
+            return Err(1);
+        }
+
+        let mut number = String::new();
+        while let Some(&character) = characters.peek() {
+            if character.is_ascii_uppercase() {
+                break;
+            }
+
+            number.push(character);
+            characters.next();
+        }
+
+        let value = number.parse().map_err(|_| 2)?;
+        words.push((letter, value));
+    }
+
+    Ok(words)
+}
+
+impl io::Read for Simulator {
+    /// Executes the received messages and reads the responses that were not
+    /// read yet. Like a serial port, this fails with a timeout if there is
+    /// nothing to read, because the simulator will never respond without
+    /// being sent something first.
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.execute_received();
+
+        if self.output.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "simulated GRBL has nothing to respond",
+            ));
+        }
+
+        let length = buffer.len().min(self.output.len());
+        for (target, byte) in buffer.iter_mut().zip(self.output.drain(..length))
+        {
+            *target = byte;
+        }
+
+        Ok(length)
+    }
+}
+
+impl io::Write for Simulator {
+    /// Receives the given bytes. Messages are kept in the receive buffer until
+    /// the responses are read, while realtime commands are handled immediately.
+    /// Fails if a message does not fit into the receive buffer. Jogging is not
+    /// simulated, so cancelling it has no effect.
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        for &byte in buffer {
+            match byte {
+                STATUS_QUERY => self.report_status(),
+                FEED_HOLD => self.is_held = !self.is_locked,
+                CYCLE_START => self.is_held = false,
+                SOFT_RESET => self.reset(),
+                // Other realtime commands like jog cancel or overrides.
+                0x80..=0xff => (),
+                b'\r' => (),
+                _ if self.get_buffered_length() == RX_BUFFER_SIZE => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        "receive buffer of simulated GRBL overflowed",
+                    ));
+                }
+                b'\n' => {
+                    let message = String::from_utf8_lossy(&self.input);
+
+                    self.received.push_back(message.into_owned());
+                    self.input.clear();
+                }
+                _ => self.input.push(byte),
+            }
+        }
+
+        Ok(buffer.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use crate::pyo3::prelude::*;
+
+use super::MachineState;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_status_report_with_machine_position() {
+        let status = Status::parse(
+            "<Idle|MPos:1.000,2.500,-3.000|FS:0,0|Bf:15,128>\r\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            status,
+            Status {
+                state: MachineState::Idle,
+                machine_position: Some([1.0, 2.5, -3.0]),
+                work_position: None,
+                feed_rate: Some(0.0),
+                spindle_speed: Some(0.0),
+                available_blocks: Some(15),
+                available_bytes: Some(128),
+            }
+        );
+    }
+
+    #[test]
+    fn derives_missing_position_from_work_coordinate_offset() {
+        let status = Status::parse(
+            "<Hold:0|WPos:1.000,1.000,0.000|F:500|WCO:2.000,3.000,0.000>",
+        )
+        .unwrap();
+
+        assert_eq!(status.state, MachineState::Hold);
+        assert_eq!(status.work_position, Some([1.0, 1.0, 0.0]));
+        assert_eq!(status.machine_position, Some([3.0, 4.0, 0.0]));
+        assert_eq!(status.feed_rate, Some(500.0));
+        assert_eq!(status.spindle_speed, None);
+    }
+
+    #[test]
+    fn ignores_other_messages() {
+        assert_eq!(Status::parse("ok\r\n"), None);
+        assert_eq!(Status::parse("<Unknown|MPos:0,0,0>\r\n"), None);
+    }
+}
+
+/// A status report of the GRBL, as it is sent in response to the '?' realtime
+/// command. Apart from the state, the GRBL only reports the fields that are
+/// enabled in its settings, so all of them are optional.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Status {
+    pub state: MachineState,
+    /// The position in the machine coordinate system.
+    pub machine_position: Option<[f64; 3]>,
+    /// The position in the work coordinate system.
+    pub work_position: Option<[f64; 3]>,
+    pub feed_rate: Option<f64>,
+    pub spindle_speed: Option<f64>,
+    /// Number of blocks that are still free in the planner buffer.
+    pub available_blocks: Option<usize>,
+    /// Number of bytes that are still free in the receive buffer.
+    pub available_bytes: Option<usize>,
+}
+
+impl Status {
+    /// Parses a status report in the format used by GRBL 1.1, e.g.
+    /// "<Idle|MPos:0.000,0.000,0.000|FS:0,0|Bf:15,128>". Returns `None` if the
+    /// line is not a status report.
+    pub fn parse(line: &str) -> Option<Status> {
+        let line = line.trim_end();
+        if !line.starts_with('<') || !line.ends_with('>') {
+            return None;
+        }
+
+        let mut fields = line[1..line.len() - 1].split('|');
+        let state = MachineState::parse(fields.next()?)?;
+
+        let mut status = Status {
+            state,
+            machine_position: None,
+            work_position: None,
+            feed_rate: None,
+            spindle_speed: None,
+            available_blocks: None,
+            available_bytes: None,
+        };
+        let mut offset = None;
+
+        for field in fields {
+            let mut parts = field.splitn(2, ':');
+            let (name, values) = match (parts.next(), parts.next()) {
+                (Some(name), Some(values)) => (name, values),
+                _ => continue,
+            };
+
+            match name {
+                "MPos" => status.machine_position = parse_position(values),
+                "WPos" => status.work_position = parse_position(values),
+                "WCO" => offset = parse_position(values),
+                "F" => status.feed_rate = values.parse().ok(),
+                "FS" => {
+                    if let Some((feed_rate, spindle_speed)) =
+                        parse_pair::<f64>(values)
+                    {
+                        status.feed_rate = feed_rate.into();
+                        status.spindle_speed = spindle_speed.into();
+                    }
+                }
+                "Bf" => {
+                    if let Some((blocks, bytes)) = parse_pair::<usize>(values) {
+                        status.available_blocks = blocks.into();
+                        status.available_bytes = bytes.into();
+                    }
+                }
+                // Fields like pin states or overrides are not of interest.
+                _ => (),
+            }
+        }
+
+        // The GRBL reports only one of the positions, together with the offset
+        // between them every once in a while. The position in the work
+        // coordinate system is the machine position minus the offset.
+        if let Some(offset) = offset {
+            let add = |a: [f64; 3], b: [f64; 3], sign: f64| {
+                [a[0] + sign * b[0], a[1] + sign * b[1], a[2] + sign * b[2]]
+            };
+
+            match (status.machine_position, status.work_position) {
+                (Some(position), None) => {
+                    status.work_position = add(position, offset, -1.0).into()
+                }
+                (None, Some(position)) => {
+                    status.machine_position = add(position, offset, 1.0).into()
+                }
+                _ => (),
+            }
+        }
+
+        status.into()
+    }
+}
+
+/// Parses the first three coordinates of a position like "1.000,2.000,0.000".
+fn parse_position(values: &str) -> Option<[f64; 3]> {
+    let mut coordinates = values.split(',').map(str::parse);
+
+    match (coordinates.next(), coordinates.next(), coordinates.next()) {
+        (Some(Ok(x)), Some(Ok(y)), Some(Ok(z))) => [x, y, z].into(),
+        _ => None,
+    }
+}
+
+/// Parses two comma separated values like "15,128".
+fn parse_pair<T: std::str::FromStr>(values: &str) -> Option<(T, T)> {
+    let mut values = values.split(',').map(str::parse);
+
+    match (values.next(), values.next()) {
+        (Some(Ok(first)), Some(Ok(second))) => (first, second).into(),
+        _ => None,
+    }
+}
+
+impl IntoPyObject for Status {
+    /// Converts the status into a `dict` containing each of its fields, with
+    /// the positions given as tuples and missing fields set to `None`.
+    fn into_object(self, py: Python) -> PyObject {
+        let dict = PyDict::new(py);
+        let to_tuple =
+            |position: [f64; 3]| (position[0], position[1], position[2]);
+
+        // Setting items only fails for keys that can not be hashed.
+        dict.set_item("state", self.state.get_name()).unwrap();
+        dict.set_item("machine_position", self.machine_position.map(to_tuple))
+            .unwrap();
+        dict.set_item("work_position", self.work_position.map(to_tuple))
+            .unwrap();
+        dict.set_item("feed_rate", self.feed_rate).unwrap();
+        dict.set_item("spindle_speed", self.spindle_speed).unwrap();
+        dict.set_item("available_blocks", self.available_blocks)
+            .unwrap();
+        dict.set_item("available_bytes", self.available_bytes)
+            .unwrap();
+
+        dict.to_object(py)
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{
+    data::{IntersectionIndex, Size},
+    pyo3::prelude::*,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: [f64; 2], expected: [f64; 2]) {
+        let distance = (actual[0] - expected[0]).hypot(actual[1] - expected[1]);
+        assert!(distance < 1e-9, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn maps_grid_with_origin_and_rotation() {
+        let transform = Transform::new([10.0, 20.0], 5.0, 0.0);
+        assert_close(transform.apply([2.0, 1.0]), [20.0, 15.0]);
+
+        let rotated = Transform::new([10.0, 20.0], 5.0, 90.0);
+        assert_close(rotated.apply([1.0, 0.0]), [10.0, 25.0]);
+        assert_close(rotated.apply([0.0, 1.0]), [15.0, 20.0]);
+    }
+
+    #[test]
+    fn maps_grid_onto_three_corners() {
+        let size = Size {
+            width: 4,
+            height: 2,
+        };
+        let corners = [[0.0, 10.0], [8.0, 12.0], [1.0, 0.0]];
+        let transform = Transform::from_corners(&size, &corners).unwrap();
+
+        assert_close(transform.apply([0.0, 0.0]), [0.0, 10.0]);
+        assert_close(transform.apply([4.0, 0.0]), [8.0, 12.0]);
+        assert_close(transform.apply([0.0, 2.0]), [1.0, 0.0]);
+        assert_close(transform.apply([4.0, 2.0]), [9.0, 2.0]);
+    }
+
+    #[test]
+    fn maps_grid_onto_four_corners_in_perspective() {
+        let size = Size {
+            width: 2,
+            height: 2,
+        };
+        let corners = [[0.0, 10.0], [10.0, 10.0], [2.0, 0.0], [8.0, 0.0]];
+        let transform = Transform::from_corners(&size, &corners).unwrap();
+
+        let index = IntersectionIndex { row: 2, column: 2 };
+        assert_close(transform.map_intersection(&index), [8.0, 0.0]);
+        assert_close(transform.apply([0.0, 2.0]), [2.0, 0.0]);
+
+        // Lines through opposite corners still meet in the center.
+        assert_close(transform.apply([1.0, 1.0]), [5.0, 3.75]);
+    }
+
+    #[test]
+    fn rejects_corners_on_one_line() {
+        let size = Size {
+            width: 1,
+            height: 1,
+        };
+        let corners = [[0.0, 0.0], [1.0, 1.0], [2.0, 2.0]];
+
+        assert_eq!(Transform::from_corners(&size, &corners), None);
+    }
+}
+
+/// Maps points in grid units, given as column and row, onto the coordinates of
+/// the machine. The grid is projected onto the plane with a homography, which
+/// covers moving, scaling, rotating and skewing it as well as viewing it in
+/// perspective.
+#[pyclass]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transform {
+    /// The matrix that is applied to the point in homogeneous coordinates.
+    matrix: [[f64; 3]; 3],
+}
+
+impl Transform {
+    /// Creates a `Transform` that places the top left intersection at the
+    /// `origin`, with `edge_length` between neighbouring intersections. The
+    /// grid is rotated counterclockwise by `rotation` degrees. Without
+    /// rotation, the rows of the grid are drawn in the negative direction of
+    /// the y-axis, so the grid appears the same way it is printed on the paper.
+    pub fn new(origin: [f64; 2], edge_length: f64, rotation: f64) -> Transform {
+        let (sin, cos) = rotation.to_radians().sin_cos();
+
+        Transform {
+            matrix: [
+                [edge_length * cos, edge_length * sin, origin[0]],
+                [edge_length * sin, -edge_length * cos, origin[1]],
+                [0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    /// Creates a `Transform` that maps the corners of a grid of the given size
+    /// onto the measured `corners`, in the order top left, top right and
+    /// bottom left. Three corners describe an affine transform, which maps the
+    /// grid onto a parallelogram, while the bottom right corner may be given
+    /// as well for a perspective transform. Returns `None` if the corners do
+    /// not span a convex quadrilateral.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are not either three or four corners.
+    pub fn from_corners(
+        size: &Size,
+        corners: &[[f64; 2]],
+    ) -> Option<Transform> {
+        assert!(corners.len() == 3 || corners.len() == 4);
+
+        let (top_left, top_right, bottom_left) =
+            (corners[0], corners[1], corners[2]);
+        let bottom_right = corners.get(3).cloned().unwrap_or([
+            top_right[0] + bottom_left[0] - top_left[0],
+            top_right[1] + bottom_left[1] - top_left[1],
+        ]);
+
+        // Maps the unit square onto the corners, as described by Paul
+        // Heckbert in "Fundamentals of Texture Mapping and Image Warping".
+        let delta = |index: usize| {
+            let sum = top_left[index] - top_right[index] + bottom_right[index]
+                - bottom_left[index];
+            let right = top_right[index] - bottom_right[index];
+            let bottom = bottom_left[index] - bottom_right[index];
+
+            (sum, right, bottom)
+        };
+        let (sum_x, right_x, bottom_x) = delta(0);
+        let (sum_y, right_y, bottom_y) = delta(1);
+
+        let determinant = right_x * bottom_y - bottom_x * right_y;
+        if determinant == 0.0 {
+            return None;
+        }
+
+        let g = (sum_x * bottom_y - bottom_x * sum_y) / determinant;
+        let h = (right_x * sum_y - sum_x * right_y) / determinant;
+
+        let mut matrix = [[0.0; 3]; 3];
+        for index in 0..2 {
+            matrix[index] = [
+                top_right[index] - top_left[index] + g * top_right[index],
+                bottom_left[index] - top_left[index] + h * bottom_left[index],
+                top_left[index],
+            ];
+        }
+        matrix[2] = [g, h, 1.0];
+
+        // The homography flips the quadrilateral inside out if the points at
+        // infinity lie between the corners, which happens for corners that are
+        // not convex, and collapses it if the corners lie on a line.
+        let is_convex = [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]]
+            .iter()
+            .all(|point| g * point[0] + h * point[1] + 1.0 > 0.0);
+        let area = (top_right[0] - top_left[0])
+            * (bottom_left[1] - top_left[1])
+            - (bottom_left[0] - top_left[0]) * (top_right[1] - top_left[1]);
+
+        if !is_convex || area == 0.0 {
+            return None;
+        }
+
+        // Scale the grid down onto the unit square first.
+        for row in &mut matrix {
+            row[0] /= size.width as f64;
+            row[1] /= size.height as f64;
+        }
+
+        Transform { matrix }.into()
+    }
+
+    /// Maps a point given as column and row, which may lie between the
+    /// intersections.
+    pub fn apply(&self, point: [f64; 2]) -> [f64; 2] {
+        let project =
+            |row: &[f64; 3]| row[0] * point[0] + row[1] * point[1] + row[2];
+        let weight = project(&self.matrix[2]);
+
+        [
+            project(&self.matrix[0]) / weight,
+            project(&self.matrix[1]) / weight,
+        ]
+    }
+
+    /// Maps the position of an intersection.
+    pub fn map_intersection(&self, index: &IntersectionIndex) -> [f64; 2] {
+        self.apply([index.column as f64, index.row as f64])
+    }
+}
+
+#[pymethods]
+impl Transform {
+    /// Creates a transform that either places the top left intersection at
+    /// `origin` with `edge_length` between neighbouring intersections,
+    /// optionally rotated counterclockwise by `rotation` degrees, or that maps
+    /// a grid of the given `size`, a `(width, height)` tuple, onto measured
+    /// `corners`. The corners are given as three or four `(x, y)` tuples in
+    /// the order top left, top right, bottom left and bottom right, where the
+    /// last one allows for perspective.
+    #[new]
+    fn __new__(
+        obj: &PyRawObject,
+        origin: Option<(f64, f64)>,
+        edge_length: Option<f64>,
+        rotation: Option<f64>,
+        corners: Option<Vec<(f64, f64)>>,
+        size: Option<(usize, usize)>,
+    ) -> PyResult<()> {
+        let transform = match (origin, edge_length, corners, size) {
+            (Some(origin), Some(edge_length), None, None) => {
+                if !(edge_length > 0.0) {
+                    return Err(exc::ValueError::new(
+                        "edge length must be positive",
+                    ));
+                }
+
+                let origin = [origin.0, origin.1];
+                Transform::new(origin, edge_length, rotation.unwrap_or(0.0))
+            }
+            (None, None, Some(corners), Some((width, height))) => {
+                if corners.len() != 3 && corners.len() != 4 {
+                    return Err(exc::ValueError::new(
+                        "expected three or four corners",
+                    ));
+                }
+
+                if width == 0 || height == 0 || rotation.is_some() {
+                    return Err(exc::ValueError::new(
+                        "size must not be empty and corners can not be rotated",
+                    ));
+                }
+
+                let corners: Vec<_> =
+                    corners.into_iter().map(|(x, y)| [x, y]).collect();
+                let size = Size { width, height };
+
+                Transform::from_corners(&size, &corners).ok_or_else(|| {
+                    exc::ValueError::new("corners do not span a quadrilateral")
+                })?
+            }
+            _ => return Err(exc::ValueError::new(
+                "expected either origin and edge length or corners and size",
+            )),
+        };
+
+        obj.init(move |_| transform)
+    }
+
+    /// Maps a point given as column and row to an `(x, y)` tuple.
+    fn map_point(&self, column: f64, row: f64) -> PyResult<(f64, f64)> {
+        let point = self.apply([column, row]);
+        Ok((point[0], point[1]))
+    }
+
+    /// Maps each point of a route, as returned by `solve`, to an `(x, y)`
+    /// tuple.
+    fn map_route(
+        &self,
+        route: Vec<(usize, usize)>,
+    ) -> PyResult<Vec<(f64, f64)>> {
+        let route = route.into_iter().map(|(column, row)| {
+            let point = self.apply([column as f64, row as f64]);
+            (point[0], point[1])
+        });
+
+        Ok(route.collect())
+    }
+}
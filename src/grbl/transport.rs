@@ -0,0 +1,11 @@
+use crate::std::io;
+
+use super::{serialport::SerialPort, Simulator};
+
+/// A connection to a GRBL over which bytes are exchanged. Reading should time
+/// out with an error if the GRBL does not respond, instead of blocking forever.
+pub trait Transport: io::Read + io::Write + Send {}
+
+impl Transport for Box<dyn SerialPort> {}
+
+impl Transport for Simulator {}
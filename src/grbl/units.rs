@@ -0,0 +1,36 @@
+use crate::pyo3::prelude::*;
+
+use self::Units::*;
+
+/// `Enum` that represents the unit in which coordinates and feed rates are
+/// given to the GRBL.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Units {
+    Millimeters,
+    Inches,
+}
+
+impl Units {
+    /// Returns the G-code command that selects the unit.
+    pub fn get_gcode(self) -> &'static str {
+        match self {
+            Millimeters => "G21",
+            Inches => "G20",
+        }
+    }
+}
+
+impl<'a> FromPyObject<'a> for Units {
+    fn extract(object: &'a PyObjectRef) -> PyResult<Self> {
+        let value = String::extract(object)?;
+
+        match value.as_str() {
+            "mm" => Ok(Millimeters),
+            "inch" => Ok(Inches),
+            _ => Err(exc::ValueError::new(format!(
+                "unknown units '{}', expected 'mm' or 'inch'",
+                value
+            ))),
+        }
+    }
+}
@@ -8,18 +8,239 @@ extern crate pyo3;
 
 mod algorithm;
 mod data;
+mod generator;
+mod grading;
 mod grbl;
+mod planner;
+mod sheet;
+mod smoothing;
+mod svg;
 
 use pyo3::prelude::*;
 
 #[pyfunction]
 fn solve(puzzle: data::Cells) -> PyResult<Vec<(usize, usize)>> {
-    let edges = algorithm::solve(&puzzle);
+    let edges = algorithm::solve(&puzzle)?;
     Ok(edges.get_route())
 }
 
+/// Returns the edges of the solution, in the same format that is accepted by
+/// `hint` and `format_puzzle`, e.g. for creating a `Program` that draws them.
+#[pyfunction]
+fn solve_edges(puzzle: data::Cells) -> PyResult<data::Edges> {
+    algorithm::solve(&puzzle).map_err(Into::into)
+}
+
+/// Returns the route of the solution together with the steps that lead to it.
+/// Each step is a tuple of the name of the rule, the edge as a tuple of its
+/// direction, row and column, the value of the edge and an explanation.
+#[pyfunction]
+fn solve_with_trace(
+    puzzle: data::Cells,
+) -> PyResult<(Vec<(usize, usize)>, Vec<data::Step>)> {
+    let (edges, trace) = algorithm::solve_with_trace(&puzzle)?;
+    Ok((edges.get_route(), trace))
+}
+
+/// Returns the easiest step that can be deduced from the edges drawn so far,
+/// in the same format as the steps returned by `solve_with_trace`, or `None` if
+/// no rule applies. The edges are given as a tuple of the rows of horizontal
+/// and the rows of vertical edges.
+#[pyfunction]
+fn hint(
+    puzzle: data::Cells,
+    edges: data::Edges,
+) -> PyResult<Option<data::Step>> {
+    puzzle.check_same_size(Some(&edges))?;
+
+    algorithm::hint(&puzzle, &edges).map_err(Into::into)
+}
+
+/// Returns the edges of up to `limit` different solutions of the puzzle, each
+/// in the same format as returned by `solve_edges`.
+#[pyfunction]
+fn solve_all(puzzle: data::Cells, limit: usize) -> PyResult<Vec<data::Edges>> {
+    Ok(algorithm::solve_all(&puzzle, limit))
+}
+
+/// Returns a route along each connected component of lines in the edges, e.g.
+/// of a partial solution. Closed loops end where they started, while open
+/// paths lead from one of their ends to the other.
+#[pyfunction]
+fn trace_routes(edges: data::Edges) -> PyResult<Vec<Vec<(usize, usize)>>> {
+    Ok(edges.get_routes())
+}
+
+/// Orders polylines, given as lists of `(x, y)` tuples, for drawing them one
+/// after another with as little travel in between as possible, starting at
+/// `start` or the origin. Open polylines may be reversed and closed ones, which
+/// end where they start, may be rotated.
+#[pyfunction]
+fn plan_paths(
+    polylines: Vec<Vec<(f64, f64)>>,
+    start: Option<(f64, f64)>,
+) -> PyResult<Vec<Vec<(f64, f64)>>> {
+    let to_points = |polyline: Vec<(f64, f64)>| {
+        polyline.into_iter().map(|(x, y)| [x, y]).collect()
+    };
+    let to_tuples = |polyline: Vec<[f64; 2]>| {
+        polyline
+            .into_iter()
+            .map(|point| (point[0], point[1]))
+            .collect()
+    };
+
+    let polylines: Vec<Vec<[f64; 2]>> =
+        polylines.into_iter().map(to_points).collect();
+    let start = start.map_or([0.0, 0.0], |(x, y)| [x, y]);
+
+    let plan = planner::plan(polylines, start);
+    Ok(plan.into_iter().map(to_tuples).collect())
+}
+
+/// Returns the strokes that draw the puzzle on paper, i.e. a dot at every
+/// intersection and the digit of every number, as lists of `(x, y)` tuples in
+/// the units of the grid. If `edges` are given, small crosses are added on the
+/// edges that are marked with an x.
+#[pyfunction]
+fn draw_sheet(
+    puzzle: data::Cells,
+    edges: Option<data::Edges>,
+) -> PyResult<Vec<Vec<(f64, f64)>>> {
+    puzzle.check_same_size(edges.as_ref())?;
+
+    let strokes = sheet::draw_sheet(&puzzle, edges.as_ref());
+    Ok(strokes
+        .into_iter()
+        .map(|stroke| {
+            stroke
+                .into_iter()
+                .map(|point| (point[0], point[1]))
+                .collect()
+        })
+        .collect())
+}
+
+/// Returns how many different solutions the puzzle has, counting no further
+/// than `limit`.
+#[pyfunction]
+fn count_solutions(puzzle: data::Cells, limit: usize) -> PyResult<usize> {
+    Ok(algorithm::solve_all(&puzzle, limit).len())
+}
+
+/// Returns whether the puzzle has exactly one solution.
+#[pyfunction]
+fn has_unique_solution(puzzle: data::Cells) -> PyResult<bool> {
+    Ok(algorithm::has_unique_solution(&puzzle))
+}
+
+/// Grades the puzzle by the rules required to solve it. Returns a `dict` with
+/// the number of edges found by each tier of rules, the backtracking depth, a
+/// score and the difficulty, which is one of "easy", "medium" or "hard".
+#[pyfunction]
+fn grade(puzzle: data::Cells) -> PyResult<data::Grade> {
+    grading::grade(&puzzle).map_err(Into::into)
+}
+
+/// Generates a random puzzle with a unique solution and the given difficulty,
+/// which is one of "easy", "medium" or "hard". The same seed always leads to
+/// the same puzzle. Raises a `ValueError` if no puzzle with a unique solution
+/// can be found for the size, e.g. for a single cell.
+#[pyfunction]
+fn generate(
+    width: usize,
+    height: usize,
+    difficulty: data::Difficulty,
+    seed: u64,
+) -> PyResult<data::Cells> {
+    if width == 0 || height == 0 {
+        return Err(exc::ValueError::new("puzzle must not be empty"));
+    }
+
+    let size = data::Size { width, height };
+    generator::generate(&size, difficulty, seed).ok_or_else(|| {
+        exc::ValueError::new("no puzzle with a unique solution was found")
+    })
+}
+
+/// Reads a puzzle in the ASCII format, returning both the numbers and the
+/// edges drawn in it. Raises a `ValueError` naming the line and column of the
+/// first invalid character.
+#[pyfunction]
+fn parse_puzzle(text: String) -> PyResult<(data::Cells, data::Edges)> {
+    let grid = data::AsciiGrid::parse(&text)?;
+    Ok((grid.get_cells(), grid.get_edges()))
+}
+
+/// Writes a puzzle in the ASCII format that is read by `parse_puzzle`. The
+/// edges are optional and left out if `None` is passed instead.
+#[pyfunction]
+fn format_puzzle(
+    puzzle: data::Cells,
+    edges: Option<data::Edges>,
+) -> PyResult<String> {
+    puzzle.check_same_size(edges.as_ref())?;
+
+    Ok(data::Board::new(puzzle, edges).to_string())
+}
+
+/// Renders the puzzle as an SVG image, e.g. for a web page, with the lines and
+/// crosses of the `edges` if they are given. The `cell_size` and the
+/// `stroke_width` are given in pixels and default to 40 and 4. Crosses are
+/// drawn unless `crosses` is `False`. The `highlighted` edges are drawn in red
+/// and given as tuples of their direction, row and column, like the edges in
+/// the steps of a solution. Highlighted edges that are still unknown are drawn
+/// as faint dashed lines.
+#[pyfunction]
+fn render_svg(
+    puzzle: data::Cells,
+    edges: Option<data::Edges>,
+    cell_size: Option<f64>,
+    stroke_width: Option<f64>,
+    crosses: Option<bool>,
+    highlighted: Option<Vec<data::EdgeIndex>>,
+) -> PyResult<String> {
+    puzzle.check_same_size(edges.as_ref())?;
+
+    let is_positive =
+        |value: Option<f64>| value.map_or(true, |value| value > 0.0);
+    if !is_positive(cell_size) || !is_positive(stroke_width) {
+        return Err(exc::ValueError::new("sizes must be positive"));
+    }
+
+    let default = svg::Style::default();
+    let style = svg::Style {
+        cell_size: cell_size.unwrap_or(default.cell_size),
+        stroke_width: stroke_width.unwrap_or(default.stroke_width),
+        show_crosses: crosses.unwrap_or(default.show_crosses),
+        highlighted: highlighted.unwrap_or(default.highlighted),
+    };
+
+    Ok(svg::render(&puzzle, edges.as_ref(), &style))
+}
+
 #[pymodinit]
-fn libsuriza(_py: Python, m: &PyModule) -> PyResult<()> {
+fn libsuriza(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_function!(solve))?;
-    m.add_class::<grbl::GRBL>()
+    m.add_function(wrap_function!(solve_edges))?;
+    m.add_function(wrap_function!(solve_with_trace))?;
+    m.add_function(wrap_function!(hint))?;
+    m.add_function(wrap_function!(solve_all))?;
+    m.add_function(wrap_function!(trace_routes))?;
+    m.add_function(wrap_function!(plan_paths))?;
+    m.add_function(wrap_function!(draw_sheet))?;
+    m.add_function(wrap_function!(count_solutions))?;
+    m.add_function(wrap_function!(has_unique_solution))?;
+    m.add_function(wrap_function!(grade))?;
+    m.add_function(wrap_function!(generate))?;
+    m.add_function(wrap_function!(parse_puzzle))?;
+    m.add_function(wrap_function!(format_puzzle))?;
+    m.add_function(wrap_function!(render_svg))?;
+    m.add_class::<data::Board>()?;
+    m.add_class::<grbl::GRBL>()?;
+    m.add_class::<grbl::Program>()?;
+    m.add_class::<grbl::Transform>()?;
+    m.add("GRBLError", py.get_type::<grbl::GRBLError>())?;
+    m.add("GRBLCommandError", py.get_type::<grbl::GRBLCommandError>())?;
+    m.add("GRBLAlarm", py.get_type::<grbl::GRBLAlarm>())
 }
@@ -0,0 +1,233 @@
+//! Ordering of polylines that are drawn one after another, so the pen travels
+//! as little as possible while it is raised
+
+use std::f64;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_travel(plan: &[Vec<[f64; 2]>], start: [f64; 2]) -> f64 {
+        let mut position = start;
+
+        plan.iter()
+            .map(|polyline| {
+                let distance = get_distance(position, polyline[0]);
+                position = polyline[polyline.len() - 1];
+                distance
+            })
+            .sum()
+    }
+
+    #[test]
+    fn enters_open_polylines_at_nearest_end() {
+        let polylines =
+            vec![vec![[10.0, 0.0], [20.0, 0.0]], vec![[5.0, 0.0], [1.0, 0.0]]];
+
+        assert_eq!(
+            plan(polylines, [0.0, 0.0]),
+            vec![vec![[1.0, 0.0], [5.0, 0.0]], vec![[10.0, 0.0], [20.0, 0.0]]]
+        );
+    }
+
+    #[test]
+    fn enters_closed_polylines_at_nearest_vertex() {
+        let square = vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        let mut polyline = square.clone();
+        polyline.push(square[0]);
+
+        assert_eq!(
+            plan(vec![polyline], [2.0, 2.0]),
+            vec![vec![
+                [1.0, 1.0],
+                [0.0, 1.0],
+                [0.0, 0.0],
+                [1.0, 0.0],
+                [1.0, 1.0]
+            ]]
+        );
+    }
+
+    #[test]
+    fn improves_on_nearest_neighbour_order() {
+        let points =
+            vec![vec![[1.0, 0.0]], vec![[-2.0, 0.0]], vec![[5.0, 0.0]]];
+        let start = [0.0, 0.0];
+
+        let nearest = order_by_nearest_neighbour(points.clone(), start);
+        assert_eq!(get_travel(&nearest, start), 11.0);
+
+        let planned = plan(points, start);
+        assert_eq!(
+            planned,
+            vec![vec![[-2.0, 0.0]], vec![[1.0, 0.0]], vec![[5.0, 0.0]]]
+        );
+        assert_eq!(get_travel(&planned, start), 9.0);
+    }
+
+    #[test]
+    fn ignores_empty_polylines() {
+        let polylines: Vec<Vec<[f64; 2]>> = vec![Vec::new()];
+        assert!(plan(polylines, [0.0, 0.0]).is_empty());
+    }
+}
+
+/// Changes in travel below this distance are not considered improvements, so
+/// rounding errors do not lead to endless swapping.
+const EPSILON: f64 = 1e-9;
+
+/// Something that is drawn in one go, from its first to its last point, and
+/// that may be entered at different points or drawn in reverse.
+pub trait Stroke {
+    /// Returns the points at which the stroke can be entered, which is empty
+    /// if there is nothing to draw.
+    fn get_entries(&self) -> Vec<[f64; 2]>;
+
+    /// Changes the stroke to start at the entry with the given index.
+    fn enter_at(&mut self, entry: usize);
+
+    /// Changes the stroke to be drawn in the opposite direction.
+    fn reverse(&mut self);
+
+    /// Returns the point at which drawing starts.
+    fn get_first(&self) -> [f64; 2];
+
+    /// Returns the point at which drawing ends.
+    fn get_last(&self) -> [f64; 2];
+}
+
+/// Polylines are drawn from their first to their last point, so open ones may
+/// be reversed, while closed ones, whose last point equals the first, may be
+/// rotated to start at any of their points.
+impl Stroke for Vec<[f64; 2]> {
+    fn get_entries(&self) -> Vec<[f64; 2]> {
+        if is_closed(self) {
+            self[..self.len() - 1].to_vec()
+        } else {
+            match (self.first(), self.last()) {
+                (Some(&first), Some(&last)) => vec![first, last],
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    fn enter_at(&mut self, entry: usize) {
+        if is_closed(self) {
+            self.pop();
+            self.rotate_left(entry);
+
+            let first = self[0];
+            self.push(first);
+        } else if entry != 0 {
+            self.reverse();
+        }
+    }
+
+    fn reverse(&mut self) {
+        self[..].reverse();
+    }
+
+    fn get_first(&self) -> [f64; 2] {
+        self[0]
+    }
+
+    fn get_last(&self) -> [f64; 2] {
+        self[self.len() - 1]
+    }
+}
+
+/// Orders the `strokes`, e.g. polylines, for drawing them one after another,
+/// starting at the given position. The order is found by always moving to the
+/// nearest entry of any stroke and then improved by reversing parts of it as
+/// long as that shortens the travel.
+pub fn plan<T: Stroke>(strokes: Vec<T>, start: [f64; 2]) -> Vec<T> {
+    let mut plan = order_by_nearest_neighbour(strokes, start);
+    improve_with_two_opt(&mut plan, start);
+
+    plan
+}
+
+fn get_distance(a: [f64; 2], b: [f64; 2]) -> f64 {
+    (a[0] - b[0]).hypot(a[1] - b[1])
+}
+
+pub fn is_closed(polyline: &[[f64; 2]]) -> bool {
+    polyline.len() > 2 && polyline[0] == polyline[polyline.len() - 1]
+}
+
+fn order_by_nearest_neighbour<T: Stroke>(
+    mut strokes: Vec<T>,
+    start: [f64; 2],
+) -> Vec<T> {
+    strokes.retain(|stroke| !stroke.get_entries().is_empty());
+
+    let mut plan = Vec::with_capacity(strokes.len());
+    let mut position = start;
+
+    while !strokes.is_empty() {
+        let mut nearest = (0, 0, f64::INFINITY);
+
+        for (index, stroke) in strokes.iter().enumerate() {
+            for (entry, &point) in stroke.get_entries().iter().enumerate() {
+                let distance = get_distance(position, point);
+
+                if distance < nearest.2 {
+                    nearest = (index, entry, distance);
+                }
+            }
+        }
+
+        let (index, entry, _) = nearest;
+        let mut stroke = strokes.remove(index);
+        stroke.enter_at(entry);
+
+        position = stroke.get_last();
+        plan.push(stroke);
+    }
+
+    plan
+}
+
+/// Reverses the order of consecutive strokes, together with the direction in
+/// which each of them is drawn, whenever that shortens the travel to and from
+/// them. Repeats until no such reversal is left.
+fn improve_with_two_opt<T: Stroke>(plan: &mut [T], start: [f64; 2]) {
+    let entry = |stroke: &T| stroke.get_first();
+    let exit = |stroke: &T| stroke.get_last();
+
+    loop {
+        let mut is_improved = false;
+
+        for first in 0..plan.len() {
+            for last in first..plan.len() {
+                let before = match first {
+                    0 => start,
+                    _ => exit(&plan[first - 1]),
+                };
+                let after = plan.get(last + 1).map(entry);
+
+                let travel_after = |position| {
+                    after.map_or(0.0, |after| get_distance(position, after))
+                };
+
+                let current = get_distance(before, entry(&plan[first]))
+                    + travel_after(exit(&plan[last]));
+                let reversed = get_distance(before, exit(&plan[last]))
+                    + travel_after(entry(&plan[first]));
+
+                if reversed < current - EPSILON {
+                    plan[first..=last].reverse();
+                    for stroke in &mut plan[first..=last] {
+                        Stroke::reverse(stroke);
+                    }
+
+                    is_improved = true;
+                }
+            }
+        }
+
+        if !is_improved {
+            return;
+        }
+    }
+}
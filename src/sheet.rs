@@ -0,0 +1,207 @@
+//! Strokes that draw a puzzle with a pen, i.e. the dots of the grid, the
+//! numbers of the cells and crosses on edges that can not contain a line. All
+//! strokes are polylines in the units of the grid, where the intersection in
+//! row `r` and column `c` is at `[c, r]`.
+
+use data::{Cell, Cells, Edge, Edges, Size};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[Vec<[f64; 2]>], expected: &[Vec<[f64; 2]>]) {
+        let points = |polylines: &[Vec<[f64; 2]>]| {
+            polylines
+                .iter()
+                .flat_map(|polyline| polyline.clone())
+                .collect()
+        };
+        let (actual, expected): (Vec<_>, Vec<_>) =
+            (points(actual), points(expected));
+
+        assert_eq!(actual.len(), expected.len());
+        for (actual, expected) in actual.iter().zip(&expected) {
+            assert!((actual[0] - expected[0]).abs() < 1e-9);
+            assert!((actual[1] - expected[1]).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn draws_dot_at_every_intersection() {
+        let dots = draw_dots(&Size {
+            width: 2,
+            height: 1,
+        });
+
+        assert_eq!(
+            dots,
+            vec![
+                vec![[0.0, 0.0]],
+                vec![[1.0, 0.0]],
+                vec![[2.0, 0.0]],
+                vec![[0.0, 1.0]],
+                vec![[1.0, 1.0]],
+                vec![[2.0, 1.0]],
+            ]
+        );
+    }
+
+    #[test]
+    fn draws_digits_centered_in_their_cells() {
+        let cells: Cells = "+ + +\n|  1|\n+ + +".parse().unwrap();
+
+        let clues = draw_clues(&cells);
+
+        assert_close(&clues, &[vec![[1.4, 0.3], [1.5, 0.25], [1.5, 0.75]]]);
+    }
+
+    #[test]
+    fn draws_crosses_only_when_asked_for() {
+        let cells: Cells = "+ + +\n|   |\n+ + +".parse().unwrap();
+        let edges: Edges = "+x+ +\n     \n+ + +".parse().unwrap();
+
+        assert_eq!(draw_sheet(&cells, None).len(), 6);
+        assert_eq!(draw_sheet(&cells, Some(&edges)).len(), 8);
+
+        assert_close(
+            &draw_crosses(&edges),
+            &[vec![[0.4, -0.1], [0.6, 0.1]], vec![[0.4, 0.1], [0.6, -0.1]]],
+        );
+    }
+}
+
+/// The height of the digits relative to the size of a cell.
+const DIGIT_HEIGHT: f64 = 0.5;
+
+/// The width of the digits relative to their height.
+const DIGIT_WIDTH: f64 = 0.6;
+
+/// Half of the width and height of the crosses drawn on edges.
+const CROSS_SIZE: f64 = 0.1;
+
+/// The digits of the built-in font, each drawn in a single stroke inside a box
+/// that is `DIGIT_WIDTH` wide and 1 high, with y pointing down like the rows of
+/// the grid.
+const ZERO: [[f64; 2]; 7] = [
+    [0.3, 0.0],
+    [0.6, 0.2],
+    [0.6, 0.8],
+    [0.3, 1.0],
+    [0.0, 0.8],
+    [0.0, 0.2],
+    [0.3, 0.0],
+];
+const ONE: [[f64; 2]; 3] = [[0.1, 0.1], [0.3, 0.0], [0.3, 1.0]];
+const TWO: [[f64; 2]; 7] = [
+    [0.0, 0.2],
+    [0.2, 0.0],
+    [0.4, 0.0],
+    [0.6, 0.2],
+    [0.6, 0.4],
+    [0.0, 1.0],
+    [0.6, 1.0],
+];
+const THREE: [[f64; 2]; 13] = [
+    [0.0, 0.1],
+    [0.15, 0.0],
+    [0.45, 0.0],
+    [0.6, 0.15],
+    [0.6, 0.35],
+    [0.45, 0.5],
+    [0.2, 0.5],
+    [0.45, 0.5],
+    [0.6, 0.65],
+    [0.6, 0.85],
+    [0.45, 1.0],
+    [0.15, 1.0],
+    [0.0, 0.9],
+];
+
+/// Returns the strokes for a sheet with the puzzle given by the `cells`, which
+/// are a dot at every intersection and the number of every cell that has one.
+/// If `edges` are given, crosses are added on those that are marked with an x.
+///
+/// # Panics
+///
+/// Panics if the `edges` do not have the same size as the `cells`.
+pub fn draw_sheet(cells: &Cells, edges: Option<&Edges>) -> Vec<Vec<[f64; 2]>> {
+    let mut strokes = draw_dots(&cells.get_size());
+    strokes.extend(draw_clues(cells));
+
+    if let Some(edges) = edges {
+        assert_eq!(edges.get_size(), cells.get_size());
+        strokes.extend(draw_crosses(edges));
+    }
+
+    strokes
+}
+
+/// Returns a dot, i.e. a polyline with a single point, for every intersection
+/// of a grid of the given `size`.
+pub fn draw_dots(&Size { width, height }: &Size) -> Vec<Vec<[f64; 2]>> {
+    iproduct!(0..=height, 0..=width)
+        .map(|(row, column)| vec![[column as f64, row as f64]])
+        .collect()
+}
+
+/// Returns the strokes of the digits of all cells that have a number, each of
+/// which is centered in its cell.
+pub fn draw_clues(cells: &Cells) -> Vec<Vec<[f64; 2]>> {
+    cells
+        .index_cells()
+        .filter_map(|index| {
+            let glyph = get_glyph(cells[&index])?;
+            let left =
+                index.column as f64 + (1.0 - DIGIT_WIDTH * DIGIT_HEIGHT) / 2.0;
+            let top = index.row as f64 + (1.0 - DIGIT_HEIGHT) / 2.0;
+
+            glyph
+                .iter()
+                .map(|point| {
+                    [
+                        left + point[0] * DIGIT_HEIGHT,
+                        top + point[1] * DIGIT_HEIGHT,
+                    ]
+                })
+                .collect::<Vec<_>>()
+                .into()
+        })
+        .collect()
+}
+
+/// Returns two strokes, which form a small cross, for every edge that can not
+/// contain a line.
+pub fn draw_crosses(edges: &Edges) -> Vec<Vec<[f64; 2]>> {
+    edges
+        .index_edges()
+        .filter(|&index| edges[index] == Edge::X)
+        .flat_map(|index| {
+            let intersections = index.get_intersections();
+            let x = (intersections[0].column + intersections[1].column) as f64
+                / 2.0;
+            let y = (intersections[0].row + intersections[1].row) as f64 / 2.0;
+
+            vec![
+                vec![
+                    [x - CROSS_SIZE, y - CROSS_SIZE],
+                    [x + CROSS_SIZE, y + CROSS_SIZE],
+                ],
+                vec![
+                    [x - CROSS_SIZE, y + CROSS_SIZE],
+                    [x + CROSS_SIZE, y - CROSS_SIZE],
+                ],
+            ]
+        })
+        .collect()
+}
+
+/// Returns the single stroke of the digit for the number of the `cell`.
+fn get_glyph(cell: Cell) -> Option<&'static [[f64; 2]]> {
+    match cell {
+        Cell::Any => None,
+        Cell::Zero => Some(&ZERO),
+        Cell::One => Some(&ONE),
+        Cell::Two => Some(&TWO),
+        Cell::Three => Some(&THREE),
+    }
+}
@@ -0,0 +1,412 @@
+//! Post-processing of polylines before they are drawn, which merges straight
+//! runs into single moves and optionally rounds the corners between them
+
+use std::iter;
+
+use planner::{self, Stroke};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_collinear_vertices() {
+        let polyline = vec![
+            [0.0, 0.0],
+            [1.0, 0.0],
+            [2.0, 0.0],
+            [2.0, 0.0],
+            [2.0, 1.0],
+            [2.0, 3.0],
+        ];
+
+        assert_eq!(
+            merge_collinear(&polyline),
+            vec![[0.0, 0.0], [2.0, 0.0], [2.0, 3.0]]
+        );
+    }
+
+    #[test]
+    fn moves_start_of_closed_polyline_to_corner() {
+        let polyline = vec![
+            [1.0, 0.0],
+            [2.0, 0.0],
+            [2.0, 1.0],
+            [0.0, 1.0],
+            [0.0, 0.0],
+            [1.0, 0.0],
+        ];
+
+        assert_eq!(
+            merge_collinear(&polyline),
+            vec![[2.0, 0.0], [2.0, 1.0], [0.0, 1.0], [0.0, 0.0], [2.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn rounds_corners_of_open_polyline() {
+        let polyline = vec![[0.0, 0.0], [10.0, 0.0], [10.0, 10.0]];
+
+        assert_eq!(
+            round_corners(&polyline, 2.0),
+            Some(Path {
+                start: [0.0, 0.0],
+                segments: vec![
+                    Segment::Line([8.0, 0.0]),
+                    Segment::Arc {
+                        end: [10.0, 2.0],
+                        center: [8.0, 2.0],
+                        is_clockwise: false,
+                    },
+                    Segment::Line([10.0, 10.0]),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn rounds_every_corner_of_closed_polyline() {
+        let polyline =
+            vec![[0.0, 0.0], [0.0, 4.0], [4.0, 4.0], [4.0, 0.0], [0.0, 0.0]];
+
+        let Path { start, segments } = round_corners(&polyline, 1.0).unwrap();
+
+        assert_eq!(start, [0.0, 1.0]);
+        assert_eq!(segments.len(), 8);
+        assert_eq!(
+            segments[7],
+            Segment::Arc {
+                end: [0.0, 1.0],
+                center: [1.0, 1.0],
+                is_clockwise: true,
+            }
+        );
+    }
+
+    #[test]
+    fn enters_rounded_path_where_a_segment_starts() {
+        let polyline =
+            vec![[0.0, 0.0], [0.0, 4.0], [4.0, 4.0], [4.0, 0.0], [0.0, 0.0]];
+        let path = round_corners(&polyline, 1.0).unwrap();
+
+        let plan = planner::plan(vec![path], [5.0, 3.5]);
+
+        assert_eq!(plan[0].start, [4.0, 3.0]);
+        assert_eq!(plan[0].segments[0], Segment::Line([4.0, 1.0]));
+        assert_eq!(plan[0].get_last(), [4.0, 3.0]);
+        assert_eq!(plan[0].segments.len(), 8);
+    }
+
+    #[test]
+    fn reverses_lines_and_arcs_of_path() {
+        let mut path =
+            round_corners(&[[0.0, 0.0], [2.0, 0.0], [2.0, 2.0]], 1.0).unwrap();
+
+        path.reverse();
+
+        assert_eq!(
+            path,
+            Path {
+                start: [2.0, 2.0],
+                segments: vec![
+                    Segment::Line([2.0, 1.0]),
+                    Segment::Arc {
+                        end: [1.0, 0.0],
+                        center: [1.0, 1.0],
+                        is_clockwise: true,
+                    },
+                    Segment::Line([0.0, 0.0]),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn does_not_round_empty_polyline() {
+        assert_eq!(round_corners(&[], 1.0), None);
+    }
+
+    #[test]
+    fn shrinks_radius_to_fit_short_segments() {
+        let polyline = vec![[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [4.0, 2.0]];
+
+        let Path { segments, .. } = round_corners(&polyline, 5.0).unwrap();
+
+        assert_eq!(
+            segments[..2],
+            [
+                Segment::Line([1.0, 0.0]),
+                Segment::Arc {
+                    end: [2.0, 1.0],
+                    center: [1.0, 1.0],
+                    is_clockwise: false,
+                }
+            ]
+        );
+    }
+}
+
+/// Relative tolerance for the cross product of two directions, below which
+/// they are considered parallel.
+const EPSILON: f64 = 1e-9;
+
+/// A part of a path that starts where the previous one ended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Segment {
+    /// A straight line to the given point.
+    Line([f64; 2]),
+    /// A circular arc around the `center` to the `end`.
+    Arc {
+        end: [f64; 2],
+        center: [f64; 2],
+        is_clockwise: bool,
+    },
+}
+
+impl Segment {
+    pub fn get_end(&self) -> [f64; 2] {
+        match *self {
+            Segment::Line(end) | Segment::Arc { end, .. } => end,
+        }
+    }
+}
+
+/// A path that is drawn from the `start` along the `segments`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Path {
+    pub start: [f64; 2],
+    pub segments: Vec<Segment>,
+}
+
+impl Path {
+    /// Returns whether the path ends where it starts.
+    pub fn is_closed(&self) -> bool {
+        self.segments.len() > 1 && self.get_last() == self.start
+    }
+}
+
+/// Like polylines, open paths may be reversed, while closed ones may be
+/// rotated to start at the start of any of their segments.
+impl Stroke for Path {
+    fn get_entries(&self) -> Vec<[f64; 2]> {
+        if self.is_closed() {
+            let ends = self.segments.iter().map(Segment::get_end);
+            iter::once(self.start)
+                .chain(ends.take(self.segments.len() - 1))
+                .collect()
+        } else {
+            vec![self.start, self.get_last()]
+        }
+    }
+
+    fn enter_at(&mut self, entry: usize) {
+        if self.is_closed() {
+            if entry > 0 {
+                self.start = self.segments[entry - 1].get_end();
+                self.segments.rotate_left(entry);
+            }
+        } else if entry != 0 {
+            self.reverse();
+        }
+    }
+
+    fn reverse(&mut self) {
+        let mut position = self.start;
+        let mut segments: Vec<_> = self
+            .segments
+            .iter()
+            .map(|segment| {
+                let reversed = match *segment {
+                    Segment::Line(_) => Segment::Line(position),
+                    Segment::Arc {
+                        center,
+                        is_clockwise,
+                        ..
+                    } => Segment::Arc {
+                        end: position,
+                        center,
+                        is_clockwise: !is_clockwise,
+                    },
+                };
+
+                position = segment.get_end();
+                reversed
+            })
+            .collect();
+        segments.reverse();
+
+        self.start = position;
+        self.segments = segments;
+    }
+
+    fn get_first(&self) -> [f64; 2] {
+        self.start
+    }
+
+    fn get_last(&self) -> [f64; 2] {
+        self.segments.last().map_or(self.start, Segment::get_end)
+    }
+}
+
+/// Removes the vertices of the `polyline` at which it does not change its
+/// direction, as well as repeated vertices, so each straight run is drawn in
+/// a single move. A closed polyline stays closed, but starts at a corner
+/// instead of in the middle of a straight run.
+pub fn merge_collinear(polyline: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    let is_closed = planner::is_closed(polyline);
+    let mut merged: Vec<[f64; 2]> = Vec::with_capacity(polyline.len());
+
+    for &point in polyline {
+        if merged.last() == Some(&point) {
+            continue;
+        }
+
+        let length = merged.len();
+        if length >= 2
+            && is_straight(merged[length - 2], merged[length - 1], point)
+        {
+            merged.pop();
+        }
+
+        merged.push(point);
+    }
+
+    let length = merged.len();
+    if is_closed
+        && length > 3
+        && is_straight(merged[length - 2], merged[0], merged[1])
+    {
+        merged.pop();
+        merged.remove(0);
+
+        let first = merged[0];
+        merged.push(first);
+    }
+
+    merged
+}
+
+/// Replaces each corner of the `polyline` by an arc of the given `radius`,
+/// which is tangent to both of its segments. The radius is reduced where a
+/// segment is too short to fit the arcs at both of its ends. Corners of closed
+/// polylines include the first point, so they start on their first segment.
+/// Returns `None` if the `polyline` is empty.
+pub fn round_corners(polyline: &[[f64; 2]], radius: f64) -> Option<Path> {
+    let polyline = merge_collinear(polyline);
+    let count = polyline.len();
+    let first = *polyline.first()?;
+
+    if count < 3 {
+        let segments = polyline[1..].iter().cloned().map(Segment::Line);
+        return Some(Path {
+            start: first,
+            segments: segments.collect(),
+        });
+    }
+
+    let is_closed = planner::is_closed(&polyline);
+    let round = |index: usize| {
+        let previous = match index {
+            0 => polyline[count - 2],
+            _ => polyline[index - 1],
+        };
+
+        get_corner(previous, polyline[index], polyline[index + 1], radius)
+    };
+
+    let mut segments = Vec::with_capacity(2 * count);
+    for index in 1..count - 1 {
+        segments.extend(round(index));
+    }
+
+    if is_closed {
+        let corner = round(0);
+        segments.extend(&corner);
+
+        Some(Path {
+            start: corner[corner.len() - 1].get_end(),
+            segments,
+        })
+    } else {
+        segments.push(Segment::Line(polyline[count - 1]));
+
+        Some(Path {
+            start: first,
+            segments,
+        })
+    }
+}
+
+fn subtract(a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn get_cross_product(a: [f64; 2], b: [f64; 2]) -> f64 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+fn get_length(vector: [f64; 2]) -> f64 {
+    vector[0].hypot(vector[1])
+}
+
+/// Returns whether the polyline goes on in the same direction at `b`.
+fn is_straight(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> bool {
+    let incoming = subtract(b, a);
+    let outgoing = subtract(c, b);
+    let tolerance = EPSILON * get_length(incoming) * get_length(outgoing);
+
+    get_cross_product(incoming, outgoing).abs() <= tolerance
+        && incoming[0] * outgoing[0] + incoming[1] * outgoing[1] > 0.0
+}
+
+/// Returns the segments that replace the corner at `b`, which are a line to
+/// the start of the arc and the arc itself. The arc takes at most half of each
+/// segment. U-turns can not be rounded, so they are kept as they are.
+fn get_corner(
+    a: [f64; 2],
+    b: [f64; 2],
+    c: [f64; 2],
+    radius: f64,
+) -> Vec<Segment> {
+    let incoming = subtract(b, a);
+    let outgoing = subtract(c, b);
+    let incoming_length = get_length(incoming);
+    let outgoing_length = get_length(outgoing);
+    let cross_product = get_cross_product(incoming, outgoing);
+
+    if cross_product.abs() <= EPSILON * incoming_length * outgoing_length {
+        return vec![Segment::Line(b)];
+    }
+
+    // The arc touches the segments at the radius times the tangent of half
+    // the angle by which the direction changes away from the corner, which is
+    // the sine divided by one plus the cosine of that angle.
+    let dot_product = incoming[0] * outgoing[0] + incoming[1] * outgoing[1];
+    let half_tangent =
+        cross_product.abs() / (incoming_length * outgoing_length + dot_product);
+    let maximum = incoming_length.min(outgoing_length) / 2.0;
+    let distance = (radius * half_tangent).min(maximum);
+    let radius = distance / half_tangent;
+
+    let towards = |direction: [f64; 2], length: f64| {
+        let scale = distance / length;
+        [b[0] + direction[0] * scale, b[1] + direction[1] * scale]
+    };
+
+    let entry = towards(incoming, -incoming_length);
+    let end = towards(outgoing, outgoing_length);
+
+    // The center lies on the normal of the incoming segment, on the side to
+    // which the polyline turns.
+    let side = cross_product.signum() * radius / incoming_length;
+    let center = [entry[0] - incoming[1] * side, entry[1] + incoming[0] * side];
+
+    vec![
+        Segment::Line(entry),
+        Segment::Arc {
+            end,
+            center,
+            is_clockwise: cross_product < 0.0,
+        },
+    ]
+}
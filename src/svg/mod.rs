@@ -0,0 +1,225 @@
+//! Rendering of puzzles and their solutions as SVG images, e.g. for showing
+//! them on a web page
+
+mod style;
+
+pub use self::style::Style;
+
+use data::{Cells, Edge, Edges, IntersectionIndex, Size};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use data::{EdgeDirection, EdgeIndex};
+
+    #[test]
+    fn draws_dots_and_numbers_of_puzzle() {
+        let cells: Cells = "+ + +\n|3  |\n+ + +".parse().unwrap();
+
+        let svg = render(&cells, None, &Style::default());
+
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"120\" \
+             height=\"80\" viewBox=\"0 0 120 80\">\n"
+        ));
+        assert_eq!(svg.matches("<circle").count(), 6);
+        assert!(svg.contains(">3</text>"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn draws_lines_crosses_and_highlighted_edges() {
+        let input = "+-+ +\n|3x  \n+-+ +";
+        let cells: Cells = input.parse().unwrap();
+        let edges: Edges = input.parse().unwrap();
+        let cross = EdgeIndex {
+            direction: EdgeDirection::Vertical,
+            row: 0,
+            column: 1,
+        };
+
+        let style = Style {
+            highlighted: vec![cross],
+            ..Style::default()
+        };
+        let svg = render(&cells, Some(&edges), &style);
+
+        assert_eq!(svg.matches("<line").count(), 3);
+        assert!(svg.contains(
+            "<line x1=\"20\" y1=\"20\" x2=\"60\" y2=\"20\" stroke=\"black\""
+        ));
+        assert!(svg.contains(&format!(
+            "<path d=\"M56 36L64 44M56 44L64 36\" stroke=\"{}\"",
+            HIGHLIGHT_COLOR
+        )));
+
+        let style = Style {
+            show_crosses: false,
+            ..Style::default()
+        };
+        assert!(!render(&cells, Some(&edges), &style).contains("<path"));
+    }
+
+    #[test]
+    fn draws_highlighted_unknown_edges_dashed() {
+        let input = "+ + +\n|    \n+ + +";
+        let cells: Cells = input.parse().unwrap();
+        let edges: Edges = input.parse().unwrap();
+        let unknown = EdgeIndex {
+            direction: EdgeDirection::Horizontal,
+            row: 0,
+            column: 1,
+        };
+
+        let style = Style {
+            highlighted: vec![unknown],
+            ..Style::default()
+        };
+        let svg = render(&cells, Some(&edges), &style);
+
+        assert_eq!(svg.matches("<line").count(), 2);
+        assert!(svg.contains(&format!(
+            "<line x1=\"60\" y1=\"20\" x2=\"100\" y2=\"20\" stroke=\"{}\" \
+             stroke-width=\"2\" stroke-dasharray=\"8 8\"",
+            HIGHLIGHT_COLOR
+        )));
+    }
+}
+
+const LINE_COLOR: &str = "black";
+const CROSS_COLOR: &str = "gray";
+const HIGHLIGHT_COLOR: &str = "#d62728";
+
+/// The opacity of highlighted edges that are still unknown, which are drawn
+/// as dashed lines.
+const UNKNOWN_OPACITY: f64 = 0.5;
+
+/// Half of the width and height of a cross relative to the size of a cell.
+const CROSS_SIZE: f64 = 0.1;
+
+/// The height of the numbers relative to the size of a cell.
+const FONT_SIZE: f64 = 0.6;
+
+/// Renders the puzzle given by the `cells` as an SVG image, with the lines and
+/// crosses of the `edges` if they are given. The image has a margin of half a
+/// cell around the grid, so the dots on its border are not cut off.
+///
+/// # Panics
+///
+/// Panics if the `edges` do not have the same size as the `cells`.
+pub fn render(cells: &Cells, edges: Option<&Edges>, style: &Style) -> String {
+    let Size { width, height } = cells.get_size();
+    let cell_size = style.cell_size;
+    let to_point = |IntersectionIndex { row, column }| {
+        [
+            (column as f64 + 0.5) * cell_size,
+            (row as f64 + 0.5) * cell_size,
+        ]
+    };
+
+    let mut elements = vec![format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" \
+         height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+        (width + 1) as f64 * cell_size,
+        (height + 1) as f64 * cell_size
+    )];
+
+    if let Some(edges) = edges {
+        assert_eq!(edges.get_size(), cells.get_size());
+
+        for index in edges.index_edges() {
+            let intersections = index.get_intersections();
+            let start = to_point(intersections[0]);
+            let end = to_point(intersections[1]);
+
+            let color = |default| {
+                if style.highlighted.contains(&index) {
+                    HIGHLIGHT_COLOR
+                } else {
+                    default
+                }
+            };
+
+            match edges[index] {
+                Edge::Line => elements.push(format!(
+                    "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" \
+                     stroke=\"{}\" stroke-width=\"{}\" \
+                     stroke-linecap=\"round\"/>",
+                    start[0],
+                    start[1],
+                    end[0],
+                    end[1],
+                    color(LINE_COLOR),
+                    style.stroke_width
+                )),
+                Edge::X if style.show_crosses => {
+                    let x = (start[0] + end[0]) / 2.0;
+                    let y = (start[1] + end[1]) / 2.0;
+                    let offset = CROSS_SIZE * cell_size;
+
+                    elements.push(format!(
+                        "<path d=\"M{} {}L{} {}M{} {}L{} {}\" stroke=\"{}\" \
+                         stroke-width=\"{}\" stroke-linecap=\"round\"/>",
+                        x - offset,
+                        y - offset,
+                        x + offset,
+                        y + offset,
+                        x - offset,
+                        y + offset,
+                        x + offset,
+                        y - offset,
+                        color(CROSS_COLOR),
+                        style.stroke_width / 2.0
+                    ))
+                }
+                Edge::Unknown if style.highlighted.contains(&index) => {
+                    let dash = 2.0 * style.stroke_width;
+
+                    elements.push(format!(
+                        "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" \
+                         stroke=\"{}\" stroke-width=\"{}\" \
+                         stroke-dasharray=\"{} {}\" opacity=\"{}\"/>",
+                        start[0],
+                        start[1],
+                        end[0],
+                        end[1],
+                        HIGHLIGHT_COLOR,
+                        style.stroke_width / 2.0,
+                        dash,
+                        dash,
+                        UNKNOWN_OPACITY
+                    ))
+                }
+                _ => (),
+            }
+        }
+    }
+
+    for (row, column) in iproduct!(0..=height, 0..=width) {
+        let center = to_point(IntersectionIndex { row, column });
+
+        elements.push(format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>",
+            center[0], center[1], style.stroke_width, LINE_COLOR
+        ));
+    }
+
+    for index in cells.index_cells() {
+        if let Some(count) = cells[&index].get_expected_line_count() {
+            elements.push(format!(
+                "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" \
+                 font-size=\"{}\" text-anchor=\"middle\" \
+                 dominant-baseline=\"central\" fill=\"{}\">{}</text>",
+                (index.column as f64 + 1.0) * cell_size,
+                (index.row as f64 + 1.0) * cell_size,
+                FONT_SIZE * cell_size,
+                LINE_COLOR,
+                count
+            ));
+        }
+    }
+
+    elements.push("</svg>".to_string());
+    elements.join("\n") + "\n"
+}
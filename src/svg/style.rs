@@ -0,0 +1,28 @@
+use data::EdgeIndex;
+
+/// Describes how a puzzle is drawn as an SVG image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Style {
+    /// The distance between neighbouring dots in pixels.
+    pub cell_size: f64,
+    /// The width of the lines in pixels, which also determines the size of
+    /// the dots and the width of the crosses.
+    pub stroke_width: f64,
+    /// Whether edges that can not contain a line are crossed out.
+    pub show_crosses: bool,
+    /// Edges that are drawn in a different color, e.g. those that were changed
+    /// by the latest step. Unknown edges among them are drawn as faint dashed
+    /// lines, so they stay visible.
+    pub highlighted: Vec<EdgeIndex>,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            cell_size: 40.0,
+            stroke_width: 4.0,
+            show_crosses: true,
+            highlighted: Vec::new(),
+        }
+    }
+}
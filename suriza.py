@@ -1,7 +1,7 @@
+import argparse
 import cv2
 import importlib.util
 import numpy as np
-import time
 
 from tesserocr import PyTessBaseAPI, PSM
 
@@ -9,6 +9,10 @@ spec = importlib.util.spec_from_file_location("libsuriza", "target/release/libsu
 libsuriza = importlib.util.module_from_spec(spec)
 spec.loader.exec_module(libsuriza)
 
+parser = argparse.ArgumentParser(description='Solves the Suriza puzzle under the camera and draws the solution.')
+parser.add_argument('--save', metavar='PATH', help='also save the G-code program to the given file')
+args = parser.parse_args()
+
 IMAGE_WIDTH = 1024
 IMAGE_HEIGHT = 768
 
@@ -90,22 +94,17 @@ def detect_digits():
 grid = detect_digits()
 
 START_X, START_Y = 56.5, 130.5
-grbl.seek_to(START_X, START_Y)
-time.sleep(1)
-
 EDGE_LENGTH = 6.5
 
-route = libsuriza.solve(grid)
-grbl.lower_pen()
+edges = libsuriza.solve_edges(grid)
+transform = libsuriza.Transform(origin=(START_X, START_Y), edge_length=EDGE_LENGTH)
+program = libsuriza.Program(edges, transform)
 
-for column, row in route:
-    x = START_X + EDGE_LENGTH * column
-    y = START_Y - EDGE_LENGTH * row
-    
-    grbl.move_to(x, y)
+if args.save:
+    program.save(args.save)
 
-grbl.raise_pen()
-grbl.seek_to(0, 0)
+# The program ends at the origin with the pen raised, where the pen is
+# lowered again for parking.
+grbl.run(program)
 grbl.lower_pen()
-
-time.sleep(5)
+grbl.wait_until_idle()